@@ -0,0 +1,574 @@
+//! Busca textual nas leis, com um índice invertido que pode ser gravado em disco.
+//!
+//! O título, o resumo e o texto de cada lei são divididos em palavras, que são indexadas sem
+//! acentos, em minúsculas e reduzidas ao radical, ignorando as stop-words. Assim `previdencia`
+//! encontra `PREVIDÊNCIA` e `pensões` encontra `pensão`.
+//!
+//! Na consulta, cada palavra é buscada separadamente, e um trecho entre aspas, como
+//! `"pensão por morte"`, só é encontrado com as palavras nessa ordem. As leis são ordenadas pela
+//! pontuação BM25 somada das palavras e trechos encontrados.
+//!
+//! O índice pode guardar a [`Origem`] das leis, para que um índice gravado seja refeito quando o
+//! arquivo ou a pasta de onde elas vieram mudar.
+
+use crate::error::Error;
+use crate::exportacao::erro_de_io;
+use crate::parser::Lei;
+use crate::radical::{palavras, Palavra};
+use crate::site::nomes_das_paginas;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+// muda quando a divisão em palavras ou os radicais mudam, invalidando os índices gravados
+const VERSAO_DO_INDICE: u32 = 1;
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const PALAVRAS_ANTES_DO_TRECHO: usize = 8;
+const PALAVRAS_NO_TRECHO: usize = 30;
+const DESTAQUE: &str = "**";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Documento {
+    id: String,
+    titulo: String,
+    resumo: String,
+    texto: String,
+    palavras: u32,
+}
+
+/// Índice invertido das leis, criado com [`Indice::new`] ou lido do disco com [`Indice::abre`].
+///
+/// ```
+/// use leis_municipais::busca::Indice;
+/// use leis_municipais::exportacao::le_leis;
+/// use std::path::Path;
+///
+/// let leis = le_leis(Path::new("resources/integration_tests/leis.json")).unwrap();
+/// let indice = Indice::new(&leis);
+///
+/// let busca = indice.busca("\"pensão por morte\"", 0, 10);
+/// assert_eq!(busca.total, 1);
+/// assert_eq!(busca.resultados[0].id, "lei-complementar-122-2019");
+/// assert!(busca.resultados[0].trecho.contains("**pensão por morte**"));
+/// ```
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Indice {
+    versao: u32,
+    #[serde(default)]
+    origem: Option<Origem>,
+    documentos: Vec<Documento>,
+    // radical -> documentos em que aparece, com as posições (em palavras) de cada ocorrência
+    termos: BTreeMap<String, Vec<(u32, Vec<u32>)>>,
+}
+
+/// Arquivo ou pasta de onde as leis do [`Indice`] foram lidas, com a quantidade de arquivos, o
+/// tamanho e a última modificação, que mudam quando as leis mudam.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Origem {
+    /// Caminho da entrada, como foi informado.
+    pub caminho: String,
+    /// Quantidade de arquivos, que é 1 quando a entrada é um arquivo.
+    pub arquivos: u64,
+    /// Soma do tamanho dos arquivos, em bytes.
+    pub bytes: u64,
+    /// Última modificação de algum dos arquivos, em nanossegundos desde 1970.
+    pub modificacao: u64,
+}
+
+/// Resultado de uma busca no [`Indice`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Busca {
+    /// Quantidade de leis encontradas.
+    pub total: usize,
+    /// As leis encontradas no intervalo pedido, da mais para a menos relevante.
+    pub resultados: Vec<Resultado>,
+}
+
+/// Uma lei encontrada na busca.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Resultado {
+    /// Id da lei, o mesmo nome das páginas do [`site`](crate::site).
+    pub id: String,
+    /// Título da lei.
+    pub titulo: String,
+    /// Pontuação BM25 da lei na busca.
+    pub pontuacao: f64,
+    /// Trecho da lei em que a busca foi encontrada, com as palavras encontradas entre `**`.
+    pub trecho: String,
+}
+
+// um documento encontrado, com as posições e os tamanhos (em palavras) de cada ocorrência
+struct Encontrado {
+    documento: u32,
+    pontuacao: f64,
+    ocorrencias: Vec<(u32, u32)>,
+}
+
+impl Origem {
+    /// Lê o tamanho e a modificação do arquivo, ou de todos os arquivos da pasta.
+    ///
+    /// # Errors
+    ///
+    /// Retorna [`Error::Diretorio`] ou [`Error::Io`] se a entrada não existir ou não puder ser
+    /// lida.
+    pub fn le(entrada: &Path) -> Result<Origem, Error> {
+        let mut origem = Origem {
+            caminho: entrada.display().to_string(),
+            arquivos: 0,
+            bytes: 0,
+            modificacao: 0,
+        };
+        for arquivo in WalkDir::new(entrada) {
+            let arquivo = arquivo.map_err(|erro| Error::Diretorio(origem.caminho.clone(), erro))?;
+            if !arquivo.file_type().is_file() {
+                continue;
+            }
+            let metadados = arquivo
+                .metadata()
+                .map_err(|erro| Error::Diretorio(origem.caminho.clone(), erro))?;
+            let modificacao = metadados
+                .modified()
+                .map_err(|erro| erro_de_io(arquivo.path(), erro))?
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duracao| {
+                    u64::try_from(duracao.as_nanos()).unwrap_or(u64::MAX)
+                });
+            origem.arquivos += 1;
+            origem.bytes += metadados.len();
+            origem.modificacao = origem.modificacao.max(modificacao);
+        }
+        Ok(origem)
+    }
+}
+
+impl Documento {
+    fn conteudo(&self) -> String {
+        format!("{}\n\n{}\n\n{}", self.titulo, self.resumo, self.texto)
+    }
+}
+
+impl Indice {
+    /// Indexa o título, o resumo e o texto das leis.
+    #[must_use]
+    pub fn new(leis: &[Lei]) -> Indice {
+        let mut documentos = Vec::with_capacity(leis.len());
+        let mut termos: BTreeMap<String, Vec<(u32, Vec<u32>)>> = BTreeMap::new();
+        for ((numero, id), lei) in (0..).zip(nomes_das_paginas(leis)).zip(leis) {
+            let mut documento = Documento {
+                id,
+                titulo: lei.titulo.clone(),
+                resumo: lei.resumo.clone(),
+                texto: lei.texto.clone(),
+                palavras: 0,
+            };
+            let palavras = palavras(&documento.conteudo());
+            for (posicao, palavra) in (0..).zip(&palavras) {
+                if let Some(termo) = &palavra.termo {
+                    let postagens = termos.entry(termo.clone()).or_default();
+                    match postagens.last_mut() {
+                        Some((ultimo, posicoes)) if *ultimo == numero => posicoes.push(posicao),
+                        _ => postagens.push((numero, vec![posicao])),
+                    }
+                }
+            }
+            documento.palavras = u32::try_from(palavras.len()).unwrap_or(u32::MAX);
+            documentos.push(documento);
+        }
+        Indice {
+            versao: VERSAO_DO_INDICE,
+            origem: None,
+            documentos,
+            termos,
+        }
+    }
+
+    /// Registra de onde as leis indexadas foram lidas.
+    #[must_use]
+    pub fn com_origem(mut self, origem: Origem) -> Self {
+        self.origem = Some(origem);
+        self
+    }
+
+    /// De onde as leis indexadas foram lidas, se isso foi registrado com [`Indice::com_origem`].
+    #[must_use]
+    pub fn origem(&self) -> Option<&Origem> {
+        self.origem.as_ref()
+    }
+
+    /// Lê um índice gravado por [`Indice::grava`].
+    ///
+    /// # Errors
+    ///
+    /// Retorna [`Error::Io`] se o arquivo não puder ser lido ou tiver sido gravado por outra
+    /// versão do índice.
+    pub fn abre(caminho: &Path) -> Result<Indice, Error> {
+        let arquivo = File::open(caminho).map_err(|erro| erro_de_io(caminho, erro))?;
+        let indice = serde_json::from_reader::<_, Indice>(BufReader::new(arquivo))
+            .map_err(|erro| erro_de_io(caminho, erro.into()))?;
+        if indice.versao != VERSAO_DO_INDICE {
+            return Err(erro_de_io(
+                caminho,
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "o índice foi gerado por outra versão e precisa ser criado novamente",
+                ),
+            ));
+        }
+        Ok(indice)
+    }
+
+    /// Grava o índice em um arquivo JSON.
+    ///
+    /// # Errors
+    ///
+    /// Retorna [`Error::Io`] se o arquivo não puder ser gravado.
+    pub fn grava(&self, caminho: &Path) -> Result<(), Error> {
+        if let Some(pasta) = caminho
+            .parent()
+            .filter(|pasta| !pasta.as_os_str().is_empty())
+        {
+            fs::create_dir_all(pasta).map_err(|erro| erro_de_io(pasta, erro))?;
+        }
+        let arquivo = File::create(caminho).map_err(|erro| erro_de_io(caminho, erro))?;
+        let mut saida = BufWriter::new(arquivo);
+        serde_json::to_writer(&mut saida, self)
+            .map_err(io::Error::from)
+            .and_then(|_| saida.flush())
+            .map_err(|erro| erro_de_io(caminho, erro))
+    }
+
+    /// Busca as leis que contêm alguma das palavras ou dos trechos entre aspas da `consulta`,
+    /// devolvendo até `limite` resultados a partir da posição `pular` da ordenação.
+    #[must_use]
+    pub fn busca(&self, consulta: &str, pular: usize, limite: usize) -> Busca {
+        let media = self.tamanho_medio();
+        let mut encontrados = BTreeMap::new();
+        for elemento in elementos(consulta) {
+            let ocorrencias = self.ocorrencias(&elemento);
+            let idf = self.idf(ocorrencias.len());
+            let tamanho = elemento
+                .last()
+                .map_or(1, |(deslocamento, _)| deslocamento + 1);
+            for (documento, inicios) in ocorrencias {
+                let encontrado = encontrados.entry(documento).or_insert_with(|| Encontrado {
+                    documento,
+                    pontuacao: 0.0,
+                    ocorrencias: Vec::new(),
+                });
+                encontrado.pontuacao += idf * self.peso(documento, inicios.len(), media);
+                encontrado
+                    .ocorrencias
+                    .extend(inicios.into_iter().map(|inicio| (inicio, tamanho)));
+            }
+        }
+
+        let mut encontrados = encontrados.into_values().collect::<Vec<Encontrado>>();
+        encontrados.sort_by(|um, outro| {
+            outro
+                .pontuacao
+                .total_cmp(&um.pontuacao)
+                .then(um.documento.cmp(&outro.documento))
+        });
+        Busca {
+            total: encontrados.len(),
+            resultados: encontrados
+                .into_iter()
+                .skip(pular)
+                .take(limite)
+                .map(|encontrado| self.resultado(&encontrado))
+                .collect(),
+        }
+    }
+
+    // posições em que o elemento começa em cada documento em que aparece inteiro
+    fn ocorrencias(&self, elemento: &[(u32, String)]) -> BTreeMap<u32, Vec<u32>> {
+        let Some((_, termo)) = elemento.first() else {
+            return BTreeMap::new();
+        };
+        self.termos
+            .get(termo)
+            .into_iter()
+            .flatten()
+            .filter_map(|(documento, posicoes)| {
+                let inicios = posicoes
+                    .iter()
+                    .copied()
+                    .filter(|inicio| {
+                        elemento[1..].iter().all(|(deslocamento, termo)| {
+                            self.contem(termo, *documento, inicio + deslocamento)
+                        })
+                    })
+                    .collect::<Vec<u32>>();
+                if inicios.is_empty() {
+                    None
+                } else {
+                    Some((*documento, inicios))
+                }
+            })
+            .collect()
+    }
+
+    fn contem(&self, termo: &str, documento: u32, posicao: u32) -> bool {
+        self.termos.get(termo).is_some_and(|documentos| {
+            documentos
+                .binary_search_by_key(&documento, |(numero, _)| *numero)
+                .is_ok_and(|indice| documentos[indice].1.binary_search(&posicao).is_ok())
+        })
+    }
+
+    fn idf(&self, encontrados: usize) -> f64 {
+        let total = quantidade(self.documentos.len());
+        let encontrados = quantidade(encontrados);
+        (1.0 + (total - encontrados + 0.5) / (encontrados + 0.5)).ln()
+    }
+
+    fn tamanho_medio(&self) -> f64 {
+        self.documentos
+            .iter()
+            .map(|documento| f64::from(documento.palavras))
+            .sum::<f64>()
+            / quantidade(self.documentos.len()).max(1.0)
+    }
+
+    fn peso(&self, documento: u32, frequencia: usize, media: f64) -> f64 {
+        let tamanho = f64::from(self.documentos[documento as usize].palavras);
+        let frequencia = quantidade(frequencia);
+        frequencia * (K1 + 1.0) / (frequencia + K1 * (1.0 - B + B * tamanho / media.max(1.0)))
+    }
+
+    fn resultado(&self, encontrado: &Encontrado) -> Resultado {
+        let documento = &self.documentos[encontrado.documento as usize];
+        Resultado {
+            id: documento.id.clone(),
+            titulo: documento.titulo.clone(),
+            pontuacao: encontrado.pontuacao,
+            trecho: trecho(documento, &encontrado.ocorrencias),
+        }
+    }
+}
+
+// cada palavra fora das aspas é um elemento, e cada trecho entre aspas é um elemento só, com
+// a distância de cada termo até o início do trecho, contando as stop-words
+fn elementos(consulta: &str) -> Vec<Vec<(u32, String)>> {
+    consulta
+        .split('"')
+        .enumerate()
+        .flat_map(|(indice, parte)| {
+            let termos = (0..)
+                .zip(palavras(parte))
+                .filter_map(|(posicao, palavra)| Some((posicao, palavra.termo?)));
+            if indice % 2 == 1 {
+                // as stop-words não são indexadas: as do começo e do fim do trecho são
+                // descartadas, e as do meio só contam nos deslocamentos
+                let termos = termos.collect::<Vec<(u32, String)>>();
+                let primeira = termos.first().map_or(0, |(posicao, _)| *posicao);
+                vec![termos
+                    .into_iter()
+                    .map(|(posicao, termo)| (posicao - primeira, termo))
+                    .collect()]
+            } else {
+                termos.map(|(_, termo)| vec![(0, termo)]).collect()
+            }
+        })
+        .filter(|elemento| !elemento.is_empty())
+        .collect()
+}
+
+// o trecho começa pouco antes da primeira ocorrência no texto da lei, ou no título ou no resumo
+// quando a busca só foi encontrada neles
+fn trecho(documento: &Documento, ocorrencias: &[(u32, u32)]) -> String {
+    let conteudo = documento.conteudo();
+    let palavras = palavras(&conteudo);
+    let destacadas = ocorrencias
+        .iter()
+        .flat_map(|(inicio, tamanho)| *inicio..inicio + tamanho)
+        .map(|posicao| posicao as usize)
+        .collect::<BTreeSet<usize>>();
+    let inicio_do_texto = documento.titulo.len() + documento.resumo.len() + 4;
+    let primeira = destacadas
+        .iter()
+        .find(|posicao| {
+            palavras
+                .get(**posicao)
+                .is_some_and(|palavra| palavra.inicio >= inicio_do_texto)
+        })
+        .or_else(|| destacadas.iter().next())
+        .copied()
+        .unwrap_or(0);
+
+    let inicio = primeira.saturating_sub(PALAVRAS_ANTES_DO_TRECHO);
+    let fim = (inicio + PALAVRAS_NO_TRECHO).min(palavras.len());
+    let mut trecho = String::new();
+    if inicio > 0 {
+        trecho.push_str("...");
+    }
+    let mut anterior: Option<&Palavra> = None;
+    for (posicao, palavra) in palavras.iter().enumerate().take(fim).skip(inicio) {
+        if let Some(anterior) = anterior {
+            trecho.push_str(&espacos_simples(&conteudo[anterior.fim..palavra.inicio]));
+        }
+        let destacada = destacadas.contains(&posicao);
+        if destacada && (posicao == inicio || !destacadas.contains(&(posicao - 1))) {
+            trecho.push_str(DESTAQUE);
+        }
+        trecho.push_str(&conteudo[palavra.inicio..palavra.fim]);
+        if destacada && (posicao + 1 == fim || !destacadas.contains(&(posicao + 1))) {
+            trecho.push_str(DESTAQUE);
+        }
+        anterior = Some(palavra);
+    }
+    if fim < palavras.len() {
+        trecho.push_str("...");
+    }
+    trecho
+}
+
+fn espacos_simples(texto: &str) -> String {
+    let mut resultado = String::with_capacity(texto.len());
+    for caractere in texto.chars() {
+        if !caractere.is_whitespace() {
+            resultado.push(caractere);
+        } else if !resultado.ends_with(' ') {
+            resultado.push(' ');
+        }
+    }
+    resultado
+}
+
+fn quantidade(valor: usize) -> f64 {
+    f64::from(u32::try_from(valor).unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::busca::{elementos, Indice, Origem};
+    use crate::exportacao::le_leis;
+    use assert_fs::TempDir;
+    use std::fs;
+    use std::path::Path;
+
+    fn indice() -> Indice {
+        Indice::new(&le_leis(Path::new("resources/integration_tests/leis.json")).unwrap())
+    }
+
+    #[test]
+    fn should_split_query_into_words_and_phrases() {
+        assert_eq!(
+            elementos("IPTU \"pensão por morte\""),
+            vec![
+                vec![(0, "iptu".to_string())],
+                vec![(0, "pensa".to_string()), (2, "mort".to_string())],
+            ]
+        );
+        assert!(elementos("por \"de\"").is_empty());
+    }
+
+    #[test]
+    fn should_ignore_stop_words_at_the_edges_of_phrases() {
+        let indice = indice();
+
+        assert_eq!(
+            elementos("\"de pensão por\""),
+            vec![vec![(0, "pensa".to_string())]]
+        );
+        assert_eq!(
+            elementos("\"de pensão por morte\""),
+            elementos("\"pensão por morte\"")
+        );
+        assert_eq!(
+            indice.busca("\"de pensão\"", 0, 10),
+            indice.busca("pensão", 0, 10)
+        );
+        assert!(indice.busca("\"de pensão\"", 0, 1).resultados[0]
+            .trecho
+            .contains(" **pensão**"));
+    }
+
+    #[test]
+    fn should_find_words_ignoring_accents_case_and_inflections() {
+        let indice = indice();
+
+        let busca = indice.busca("PREVIDENCIA", 0, 10);
+        assert_eq!(busca.total, 1);
+        assert_eq!(busca.resultados[0].id, "lei-complementar-122-2019");
+
+        assert_eq!(
+            indice.busca("pensoes", 0, 10),
+            indice.busca("pensão", 0, 10)
+        );
+        assert_eq!(indice.busca("por que", 0, 10).total, 0);
+    }
+
+    #[test]
+    fn should_rank_leis_by_bm25() {
+        let busca = indice().busca("regimento gabinete", 0, 10);
+
+        assert_eq!(busca.total, 3);
+        let ids = busca
+            .resultados
+            .iter()
+            .map(|resultado| resultado.id.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(ids[0], "decreto-5907-1995");
+        assert!(busca.resultados[0].pontuacao > busca.resultados[1].pontuacao);
+        assert_eq!(
+            indice().busca("regimento gabinete", 1, 1).resultados.len(),
+            1
+        );
+    }
+
+    #[test]
+    fn should_match_phrases_only_in_order() {
+        let indice = indice();
+
+        assert_eq!(indice.busca("\"pensao por morte\"", 0, 10).total, 1);
+        assert_eq!(indice.busca("\"morte por pensao\"", 0, 10).total, 0);
+    }
+
+    #[test]
+    fn should_highlight_the_words_found_in_the_snippet() {
+        let busca = indice().busca("\"gabinete do prefeito\"", 0, 1);
+        let trecho = &busca.resultados[0].trecho;
+
+        assert!(trecho.contains("**Gabinete do Prefeito**"));
+        assert!(trecho.starts_with("..."));
+        assert!(!trecho.contains('\n'));
+    }
+
+    #[test]
+    fn should_write_and_read_the_index() {
+        let temp = TempDir::new().unwrap();
+        let caminho = temp.path().join("leis.indice.json");
+        let origem = Origem::le(Path::new("resources/integration_tests/leis.json")).unwrap();
+        let indice = indice().com_origem(origem.clone());
+
+        indice.grava(&caminho).unwrap();
+        let lido = Indice::abre(&caminho).unwrap();
+
+        assert_eq!(lido.busca("decreto", 0, 10), indice.busca("decreto", 0, 10));
+        assert_eq!(lido.origem(), Some(&origem));
+    }
+
+    #[test]
+    fn should_change_the_origem_when_the_leis_change() {
+        let temp = TempDir::new().unwrap();
+        let arquivo = temp.path().join("leis.json");
+        fs::write(&arquivo, "[]").unwrap();
+
+        let origem = Origem::le(&arquivo).unwrap();
+        assert_eq!((origem.arquivos, origem.bytes), (1, 2));
+        assert_eq!(Origem::le(temp.path()).unwrap().arquivos, 1);
+        fs::write(&arquivo, "[ ]").unwrap();
+        assert_ne!(Origem::le(&arquivo).unwrap(), origem);
+        assert!(Origem::le(&temp.path().join("outras-leis.json")).is_err());
+    }
+}
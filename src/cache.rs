@@ -0,0 +1,253 @@
+use crate::error::Error;
+use crate::exportacao::erro_de_io;
+use crate::parser::{Lei, VERSAO_DO_PARSER};
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Conteudo {
+    versao: String,
+    codificacao: Option<String>,
+    arquivos: BTreeMap<String, Entrada>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Entrada {
+    impressao_digital: u64,
+    lei: Lei,
+}
+
+/// Leis parseadas em uma execução anterior, reaproveitadas enquanto o conteúdo, as categorias
+/// e a [`VERSAO_DO_PARSER`] forem os mesmos.
+///
+/// Dos diretórios percorridos nesta execução, só os arquivos encontrados são gravados de volta
+/// por [`Cache::grava`], para que arquivos removidos da pasta também saiam do cache. As leis de
+/// outros diretórios são mantidas como estavam.
+#[derive(Debug)]
+pub struct Cache {
+    caminho: PathBuf,
+    versao: String,
+    codificacao: Option<String>,
+    anteriores: BTreeMap<String, Entrada>,
+    atuais: Mutex<BTreeMap<String, Entrada>>,
+    percorridos: Mutex<Vec<PathBuf>>,
+}
+
+impl Cache {
+    /// Lê o cache gravado no arquivo. Um arquivo inexistente, ilegível ou gravado por outra
+    /// versão do parser ou com outra codificação forçada resulta em um cache vazio.
+    ///
+    /// # Errors
+    ///
+    /// Retorna [`Error::Io`] se o arquivo existir mas não puder ser aberto.
+    pub fn abre(caminho: &Path, codificacao: Option<&'static Encoding>) -> Result<Self, Error> {
+        let versao = format!("{}+{}", env!("CARGO_PKG_VERSION"), VERSAO_DO_PARSER);
+        let codificacao = codificacao.map(|encoding| encoding.name().to_string());
+        let anteriores = match File::open(caminho) {
+            // um cache ilegível ou de outra versão é descartado e refeito
+            Ok(arquivo) => serde_json::from_reader::<_, Conteudo>(BufReader::new(arquivo))
+                .ok()
+                .filter(|conteudo| conteudo.versao == versao && conteudo.codificacao == codificacao)
+                .map(|conteudo| conteudo.arquivos)
+                .unwrap_or_default(),
+            Err(erro) if erro.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(erro) => return Err(erro_de_io(caminho, erro)),
+        };
+        Ok(Cache {
+            caminho: caminho.to_path_buf(),
+            versao,
+            codificacao,
+            anteriores,
+            atuais: Mutex::new(BTreeMap::new()),
+            percorridos: Mutex::new(Vec::new()),
+        })
+    }
+
+    // a lei só é reaproveitada se o conteúdo e as categorias do arquivo forem os mesmos
+    pub(crate) fn busca(
+        &self,
+        file_path: &str,
+        impressao_digital: u64,
+        categorias: &[String],
+    ) -> Option<Lei> {
+        let entrada = self.anteriores.get(file_path)?;
+        if entrada.impressao_digital != impressao_digital || entrada.lei.categorias != categorias {
+            return None;
+        }
+        self.guarda(file_path, impressao_digital, &entrada.lei);
+        Some(entrada.lei.clone())
+    }
+
+    pub(crate) fn guarda(&self, file_path: &str, impressao_digital: u64, lei: &Lei) {
+        let entrada = Entrada {
+            impressao_digital,
+            lei: lei.clone(),
+        };
+        self.atuais
+            .lock()
+            .expect("Outra thread falhou ao atualizar o cache")
+            .insert(file_path.to_string(), entrada);
+    }
+
+    // os arquivos do cache que estiverem dentro do diretório e não forem encontrados nele
+    // nesta execução são descartados ao gravar
+    pub(crate) fn percorre(&self, diretorio: &str) {
+        self.percorridos
+            .lock()
+            .expect("Outra thread falhou ao atualizar o cache")
+            .push(PathBuf::from(diretorio));
+    }
+
+    /// Grava no arquivo as leis encontradas nesta execução, junto com as leis anteriores de
+    /// diretórios que não foram percorridos.
+    ///
+    /// # Errors
+    ///
+    /// Retorna [`Error::Io`] se o arquivo não puder ser gravado.
+    ///
+    /// # Panics
+    ///
+    /// Entra em pânico se uma thread do parse tiver entrado em pânico enquanto atualizava o cache.
+    pub fn grava(self) -> Result<(), Error> {
+        let mut arquivos = self
+            .atuais
+            .into_inner()
+            .expect("Outra thread falhou ao atualizar o cache");
+        let percorridos = self
+            .percorridos
+            .into_inner()
+            .expect("Outra thread falhou ao atualizar o cache");
+        for (file_path, entrada) in self.anteriores {
+            let percorrido = percorridos
+                .iter()
+                .any(|diretorio| Path::new(&file_path).starts_with(diretorio));
+            if !percorrido {
+                arquivos.entry(file_path).or_insert(entrada);
+            }
+        }
+        let conteudo = Conteudo {
+            versao: self.versao,
+            codificacao: self.codificacao,
+            arquivos,
+        };
+        if let Some(pasta) = self
+            .caminho
+            .parent()
+            .filter(|pasta| !pasta.as_os_str().is_empty())
+        {
+            fs::create_dir_all(pasta).map_err(|erro| erro_de_io(pasta, erro))?;
+        }
+        let arquivo =
+            File::create(&self.caminho).map_err(|erro| erro_de_io(&self.caminho, erro))?;
+        let mut saida = BufWriter::new(arquivo);
+        serde_json::to_writer(&mut saida, &conteudo)
+            .map_err(io::Error::from)
+            .and_then(|_| saida.flush())
+            .map_err(|erro| erro_de_io(&self.caminho, erro))
+    }
+}
+
+// FNV-1a, que ao contrário do hasher padrão tem o mesmo resultado em qualquer versão do Rust
+pub fn impressao_digital(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::{impressao_digital, Cache};
+    use crate::parser::parse_html_to_lei;
+
+    const ARQUIVO: &str =
+        "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html";
+
+    #[test]
+    fn should_reuse_lei_only_for_same_content_and_categorias() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let caminho = temp.path().join("cache.json");
+        let categorias = vec!["orgânica".to_string()];
+        let lei = parse_html_to_lei(ARQUIVO, categorias.clone(), None).unwrap();
+
+        let cache = Cache::abre(&caminho, None).unwrap();
+        assert_eq!(cache.busca(ARQUIVO, 42, &categorias), None);
+        cache.guarda(ARQUIVO, 42, &lei);
+        cache.grava().unwrap();
+
+        let cache = Cache::abre(&caminho, None).unwrap();
+        assert_eq!(cache.busca(ARQUIVO, 42, &categorias), Some(lei));
+        assert_eq!(cache.busca(ARQUIVO, 43, &categorias), None);
+        assert_eq!(cache.busca(ARQUIVO, 42, &[]), None);
+        assert_eq!(
+            Cache::abre(&caminho, Some(encoding_rs::WINDOWS_1252))
+                .unwrap()
+                .busca(ARQUIVO, 42, &categorias),
+            None
+        );
+    }
+
+    #[test]
+    fn should_drop_files_not_seen_in_the_last_run() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let caminho = temp.path().join("cache.json");
+        let lei = parse_html_to_lei(ARQUIVO, vec![], None).unwrap();
+
+        let cache = Cache::abre(&caminho, None).unwrap();
+        cache.guarda(ARQUIVO, 42, &lei);
+        cache.grava().unwrap();
+        let cache = Cache::abre(&caminho, None).unwrap();
+        cache.percorre("resources/unit_tests");
+        cache.grava().unwrap();
+
+        assert_eq!(
+            Cache::abre(&caminho, None).unwrap().busca(ARQUIVO, 42, &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn should_keep_files_of_directories_not_walked() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let caminho = temp.path().join("cache.json");
+        let lei = parse_html_to_lei(ARQUIVO, vec![], None).unwrap();
+
+        let cache = Cache::abre(&caminho, None).unwrap();
+        cache.guarda(ARQUIVO, 42, &lei);
+        cache.grava().unwrap();
+        Cache::abre(&caminho, None).unwrap().grava().unwrap();
+        let cache = Cache::abre(&caminho, None).unwrap();
+        cache.percorre("resources/integration_tests/leis");
+        cache.grava().unwrap();
+
+        assert_eq!(
+            Cache::abre(&caminho, None).unwrap().busca(ARQUIVO, 42, &[]),
+            Some(lei)
+        );
+    }
+
+    #[test]
+    fn should_discard_unreadable_cache() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let caminho = temp.path().join("cache.json");
+        std::fs::write(&caminho, "{ corrompido").unwrap();
+
+        assert!(Cache::abre(&caminho, None).is_ok());
+    }
+
+    #[test]
+    fn should_hash_content_with_fnv_1a() {
+        assert_eq!(impressao_digital(Vec::new()), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(
+            impressao_digital(b"a".iter().copied()),
+            0xaf63_dc4c_8601_ec8c
+        );
+    }
+}
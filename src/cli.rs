@@ -0,0 +1,520 @@
+use leis_municipais::exportacao::{e_saida_padrao, Coluna, Formato, OpcoesDeExportacao};
+use leis_municipais::{Encoding, Verbosidade};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+const MODELO_DE_AJUDA: &str = "{bin} {version}
+{about}
+
+USO:
+    {usage}
+
+FLAGS:
+{flags}
+
+OPÇÕES:
+{options}
+
+COMANDOS:
+{subcommands}";
+
+const MODELO_DE_AJUDA_DO_COMANDO: &str = "{bin}
+{about}
+
+USO:
+    {usage}
+
+ARGUMENTOS:
+{positionals}
+
+FLAGS:
+{flags}
+
+OPÇÕES:
+{options}";
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "leis-municipais",
+    about = "Parseia os arquivos HTML do Leis Municipais e gera as leis em formato estruturado.",
+    template = MODELO_DE_AJUDA,
+    help_message = "Exibe esta ajuda",
+    version_message = "Exibe a versão"
+)]
+pub struct Opcoes {
+    #[structopt(
+        short,
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Não exibe o relatório nem os erros de cada arquivo"
+    )]
+    pub quiet: bool,
+
+    #[structopt(
+        short,
+        long,
+        global = true,
+        help = "Exibe também cada arquivo parseado com sucesso"
+    )]
+    pub verbose: bool,
+
+    #[structopt(
+        long,
+        global = true,
+        value_name = "N",
+        help = "Quantidade de threads usadas no parse (padrão: uma por CPU)"
+    )]
+    pub threads: Option<usize>,
+
+    #[structopt(
+        long,
+        global = true,
+        value_name = "CODIFICAÇÃO",
+        parse(try_from_str = parse_encoding),
+        help = "Força a codificação de todos os arquivos (ex.: utf-8, windows-1252)"
+    )]
+    pub encoding: Option<&'static Encoding>,
+
+    #[structopt(
+        long,
+        global = true,
+        value_name = "ARQUIVO",
+        help = "Reaproveita as leis parseadas em execuções anteriores, gravadas neste arquivo, \
+                parseando novamente só os arquivos alterados"
+    )]
+    pub cache: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    pub comando: Comando,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Comando {
+    #[structopt(
+        about = "Parseia a pasta de leis e grava o resultado",
+        template = MODELO_DE_AJUDA_DO_COMANDO,
+        help_message = "Exibe esta ajuda"
+    )]
+    Parse {
+        #[structopt(
+            help = "Pasta (ou arquivo .zip, .tar ou .tar.gz) com os arquivos HTML das leis"
+        )]
+        diretorio: String,
+
+        #[structopt(flatten)]
+        saida: Saida,
+    },
+
+    #[structopt(
+        about = "Parseia a pasta de leis sem gravar nada e lista os arquivos com erro",
+        template = MODELO_DE_AJUDA_DO_COMANDO,
+        help_message = "Exibe esta ajuda"
+    )]
+    Validate {
+        #[structopt(
+            help = "Pasta (ou arquivo .zip, .tar ou .tar.gz) com os arquivos HTML das leis"
+        )]
+        diretorio: String,
+    },
+
+    #[structopt(
+        about = "Exibe estatísticas das leis da pasta por diretório e por tipo de norma",
+        template = MODELO_DE_AJUDA_DO_COMANDO,
+        help_message = "Exibe esta ajuda"
+    )]
+    Stats {
+        #[structopt(
+            help = "Pasta (ou arquivo .zip, .tar ou .tar.gz) com os arquivos HTML das leis"
+        )]
+        diretorio: String,
+    },
+
+    #[structopt(
+        about = "Converte um arquivo JSON gerado pelo comando parse para outro formato",
+        template = MODELO_DE_AJUDA_DO_COMANDO,
+        help_message = "Exibe esta ajuda"
+    )]
+    Export {
+        #[structopt(
+            conflicts_with = "output",
+            help = "Arquivo onde as leis serão gravadas, ou - para a saída padrão \
+                    (padrão: leis.<formato>)"
+        )]
+        arquivo: Option<PathBuf>,
+
+        #[structopt(
+            short,
+            long,
+            value_name = "ARQUIVO",
+            default_value = "leis.json",
+            help = "Arquivo JSON ou JSONL gerado pelo comando parse"
+        )]
+        entrada: PathBuf,
+
+        #[structopt(flatten)]
+        saida: Saida,
+    },
+
+    #[structopt(
+        about = "Gera um site estático com as leis de um arquivo gerado pelo comando parse",
+        template = MODELO_DE_AJUDA_DO_COMANDO,
+        help_message = "Exibe esta ajuda"
+    )]
+    Site {
+        #[structopt(help = "Pasta onde o site será gerado")]
+        saida: PathBuf,
+
+        #[structopt(
+            short,
+            long,
+            value_name = "ARQUIVO",
+            default_value = "leis.json",
+            help = "Arquivo JSON ou JSONL gerado pelo comando parse"
+        )]
+        entrada: PathBuf,
+    },
+
+    #[structopt(
+        about = "Serve as leis por uma API HTTP com respostas em JSON em localhost",
+        template = MODELO_DE_AJUDA_DO_COMANDO,
+        help_message = "Exibe esta ajuda"
+    )]
+    Serve {
+        #[structopt(
+            default_value = "leis.json",
+            help = "Pasta (ou arquivo .zip, .tar ou .tar.gz) com os arquivos HTML das leis, \
+                    ou arquivo JSON ou JSONL gerado pelo comando parse"
+        )]
+        entrada: String,
+
+        #[structopt(
+            short,
+            long,
+            value_name = "PORTA",
+            default_value = "8000",
+            help = "Porta em que a API é servida"
+        )]
+        porta: u16,
+    },
+
+    #[structopt(
+        about = "Busca as leis que mencionam os termos, das mais para as menos relevantes",
+        template = MODELO_DE_AJUDA_DO_COMANDO,
+        help_message = "Exibe esta ajuda"
+    )]
+    Search {
+        #[structopt(
+            required = true,
+            help = "Termos da busca; use aspas para buscar uma frase, como '\"pensão por morte\"'"
+        )]
+        consulta: Vec<String>,
+
+        #[structopt(
+            short,
+            long,
+            value_name = "ARQUIVO",
+            default_value = "leis.indice.json",
+            help = "Arquivo do índice de busca"
+        )]
+        indice: PathBuf,
+
+        #[structopt(
+            short,
+            long,
+            value_name = "ENTRADA",
+            default_value = "leis.json",
+            help = "Pasta (ou arquivo .zip, .tar ou .tar.gz) com os arquivos HTML das leis, ou \
+                    arquivo JSON ou JSONL gerado pelo comando parse, de onde o índice é criado; \
+                    o índice gravado é criado novamente quando a entrada muda"
+        )]
+        entrada: String,
+
+        #[structopt(
+            short = "n",
+            long,
+            value_name = "N",
+            default_value = "10",
+            help = "Quantidade máxima de leis exibidas"
+        )]
+        limite: usize,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+pub struct Saida {
+    #[structopt(
+        short,
+        long,
+        value_name = "ARQUIVO",
+        help = "Arquivo onde as leis serão gravadas, ou - para a saída padrão (padrão: leis.<formato>)"
+    )]
+    pub output: Option<PathBuf>,
+
+    #[structopt(
+        short,
+        long,
+        value_name = "FORMATO",
+        default_value = "json",
+        possible_values = Formato::VALORES,
+        help = "Formato do arquivo gravado"
+    )]
+    pub format: Formato,
+
+    #[structopt(
+        long,
+        value_name = "COLUNAS",
+        use_delimiter = true,
+        help = "Colunas do CSV, separadas por vírgula (padrão: titulo,categoria,resumo,documento)"
+    )]
+    pub columns: Vec<Coluna>,
+
+    #[structopt(
+        long,
+        value_name = "CARACTERES",
+        help = "Trunca as colunas de texto do CSV nessa quantidade de caracteres"
+    )]
+    pub max_texto: Option<usize>,
+
+    #[structopt(long, help = "Grava o BOM do UTF-8 no início do CSV, para o Excel")]
+    pub bom: bool,
+
+    #[structopt(
+        long,
+        value_name = "LOCAL",
+        help = "Localidade das URNs do LexML (padrão: br;bahia;feira.de.santana)"
+    )]
+    pub localidade: Option<String>,
+
+    #[structopt(
+        long,
+        value_name = "ID",
+        default_value = "0",
+        help = "Número do publicador cadastrado no LexML"
+    )]
+    pub id_publicador: u32,
+}
+
+impl Saida {
+    pub fn caminho(&self) -> PathBuf {
+        self.output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(self.format.arquivo_padrao()))
+    }
+
+    // o export recebe o arquivo de saída como argumento, e não só pelo --output
+    pub fn caminho_ou(&self, arquivo: Option<&PathBuf>) -> PathBuf {
+        arquivo.cloned().unwrap_or_else(|| self.caminho())
+    }
+
+    pub fn opcoes(&self) -> OpcoesDeExportacao {
+        let padrao = OpcoesDeExportacao::default();
+        OpcoesDeExportacao {
+            colunas: if self.columns.is_empty() {
+                padrao.colunas
+            } else {
+                self.columns.clone()
+            },
+            limite_do_texto: self.max_texto,
+            bom: self.bom,
+            localidade: self.localidade.clone().unwrap_or(padrao.localidade),
+            id_publicador: self.id_publicador,
+        }
+    }
+}
+
+impl Opcoes {
+    pub fn verbosidade(&self) -> Verbosidade {
+        if self.quiet {
+            Verbosidade::Silencioso
+        } else if self.verbose {
+            Verbosidade::Detalhado
+        } else {
+            Verbosidade::Normal
+        }
+    }
+}
+
+impl Comando {
+    pub fn usa_saida_padrao(&self) -> bool {
+        match self {
+            Comando::Parse { saida, .. } => e_saida_padrao(&saida.caminho()),
+            Comando::Export { arquivo, saida, .. } => {
+                e_saida_padrao(&saida.caminho_ou(arquivo.as_ref()))
+            }
+            _ => false,
+        }
+    }
+}
+
+fn parse_encoding(rotulo: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(rotulo.trim().as_bytes())
+        .ok_or_else(|| format!("Codificação desconhecida: {}", rotulo))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cli::{Comando, Opcoes};
+    use leis_municipais::exportacao::{Coluna, Formato, OpcoesDeExportacao};
+    use leis_municipais::Verbosidade;
+    use std::path::PathBuf;
+    use structopt::StructOpt;
+
+    #[test]
+    fn should_parse_parse_command_with_defaults() {
+        let opcoes = Opcoes::from_iter(&["leis-municipais", "parse", "leis/"]);
+
+        assert_eq!(opcoes.verbosidade(), Verbosidade::Normal);
+        assert_eq!(opcoes.threads, None);
+        match opcoes.comando {
+            Comando::Parse { diretorio, saida } => {
+                assert_eq!(diretorio, "leis/");
+                assert_eq!(saida.caminho(), PathBuf::from("leis.json"));
+                assert_eq!(saida.format, Formato::Json);
+                assert_eq!(saida.opcoes(), OpcoesDeExportacao::default());
+            }
+            comando => panic!("comando inesperado: {:?}", comando),
+        }
+    }
+
+    #[test]
+    fn should_accept_global_options_after_the_command() {
+        let opcoes = Opcoes::from_iter(&[
+            "leis-municipais",
+            "validate",
+            "leis/",
+            "--quiet",
+            "--threads",
+            "2",
+            "--encoding",
+            "latin1",
+            "--cache",
+            "cache.json",
+        ]);
+
+        assert_eq!(opcoes.verbosidade(), Verbosidade::Silencioso);
+        assert_eq!(opcoes.threads, Some(2));
+        assert_eq!(opcoes.encoding, Some(encoding_rs::WINDOWS_1252));
+        assert_eq!(opcoes.cache, Some(PathBuf::from("cache.json")));
+    }
+
+    #[test]
+    fn should_parse_csv_options() {
+        let opcoes = Opcoes::from_iter(&[
+            "leis-municipais",
+            "export",
+            "--format",
+            "csv",
+            "--columns",
+            "titulo,texto",
+            "--max-texto",
+            "100",
+            "--bom",
+        ]);
+
+        match opcoes.comando {
+            Comando::Export {
+                arquivo,
+                entrada,
+                saida,
+            } => {
+                assert_eq!(
+                    saida.caminho_ou(arquivo.as_ref()),
+                    PathBuf::from("leis.csv")
+                );
+                assert_eq!(entrada, PathBuf::from("leis.json"));
+                assert_eq!(
+                    saida.opcoes(),
+                    OpcoesDeExportacao {
+                        colunas: vec![Coluna::Titulo, Coluna::Texto],
+                        limite_do_texto: Some(100),
+                        bom: true,
+                        ..OpcoesDeExportacao::default()
+                    }
+                );
+            }
+            comando => panic!("comando inesperado: {:?}", comando),
+        }
+    }
+
+    #[test]
+    fn should_parse_export_output_as_argument() {
+        let opcoes =
+            Opcoes::from_iter(&["leis-municipais", "export", "--format", "sqlite", "leis.db"]);
+
+        match opcoes.comando {
+            Comando::Export {
+                arquivo,
+                entrada,
+                saida,
+            } => {
+                assert_eq!(saida.caminho_ou(arquivo.as_ref()), PathBuf::from("leis.db"));
+                assert_eq!(entrada, PathBuf::from("leis.json"));
+                assert_eq!(saida.format, Formato::Sqlite);
+            }
+            comando => panic!("comando inesperado: {:?}", comando),
+        }
+    }
+
+    #[test]
+    fn should_parse_site_command() {
+        let opcoes = Opcoes::from_iter(&["leis-municipais", "site", "./out"]);
+
+        match opcoes.comando {
+            Comando::Site { saida, entrada } => {
+                assert_eq!(saida, PathBuf::from("./out"));
+                assert_eq!(entrada, PathBuf::from("leis.json"));
+            }
+            comando => panic!("comando inesperado: {:?}", comando),
+        }
+    }
+
+    #[test]
+    fn should_parse_serve_command() {
+        let opcoes = Opcoes::from_iter(&["leis-municipais", "serve", "leis/", "--porta", "3000"]);
+
+        match opcoes.comando {
+            Comando::Serve { entrada, porta } => {
+                assert_eq!(entrada, "leis/");
+                assert_eq!(porta, 3000);
+            }
+            comando => panic!("comando inesperado: {:?}", comando),
+        }
+    }
+
+    #[test]
+    fn should_parse_search_command() {
+        let opcoes = Opcoes::from_iter(&[
+            "leis-municipais",
+            "search",
+            "\"pensao por morte\"",
+            "iptu",
+            "-n",
+            "5",
+        ]);
+
+        match opcoes.comando {
+            Comando::Search {
+                consulta,
+                indice,
+                entrada,
+                limite,
+            } => {
+                assert_eq!(consulta, vec!["\"pensao por morte\"", "iptu"]);
+                assert_eq!(indice, PathBuf::from("leis.indice.json"));
+                assert_eq!(entrada, "leis.json");
+                assert_eq!(limite, 5);
+            }
+            comando => panic!("comando inesperado: {:?}", comando),
+        }
+    }
+
+    #[test]
+    fn should_reject_unknown_encoding() {
+        let erro =
+            Opcoes::from_iter_safe(&["leis-municipais", "stats", "leis/", "--encoding", "x"])
+                .unwrap_err();
+
+        assert!(erro.message.contains("Codificação desconhecida: x"));
+    }
+}
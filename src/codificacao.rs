@@ -0,0 +1,115 @@
+use crate::error::Error;
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use regex::bytes::Regex;
+
+lazy_static! {
+    static ref META_CHARSET_REGEX: Regex =
+        Regex::new(r#"(?i)<meta\b[^>]*?charset\s*=\s*["']?\s*(?P<charset>[a-z0-9_:.-]+)"#).unwrap();
+}
+
+// mesmo limite do pre-scan de <meta> definido pelo padrão HTML
+const INICIO_DO_DOCUMENTO: usize = 1024;
+
+pub fn decodifica(
+    bytes: &[u8],
+    forcada: Option<&'static Encoding>,
+    file_name: &str,
+) -> Result<(String, &'static Encoding), Error> {
+    let encoding = forcada.unwrap_or_else(|| detecta(bytes));
+    let (texto, com_erros) = encoding.decode_with_bom_removal(bytes);
+    if com_erros {
+        return Err(Error::Decodificacao(
+            file_name.to_string(),
+            encoding.name().to_string(),
+        ));
+    }
+    Ok((texto.into_owned(), encoding))
+}
+
+fn detecta(bytes: &[u8]) -> &'static Encoding {
+    Encoding::for_bom(bytes)
+        .map(|(encoding, _)| encoding)
+        .or_else(|| encoding_declarado(bytes))
+        .unwrap_or_else(|| {
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, true);
+            detector.guess(Some(b"br"), false)
+        })
+}
+
+fn encoding_declarado(bytes: &[u8]) -> Option<&'static Encoding> {
+    let inicio = &bytes[..bytes.len().min(INICIO_DO_DOCUMENTO)];
+    let captures = META_CHARSET_REGEX.captures(inicio)?;
+    let encoding = Encoding::for_label(&captures["charset"])?;
+    // um HTML que chegou até aqui como bytes não pode estar em UTF-16
+    if encoding == UTF_16BE || encoding == UTF_16LE {
+        Some(UTF_8)
+    } else {
+        Some(encoding)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codificacao::decodifica;
+    use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+    use std::fs;
+
+    #[test]
+    fn should_decode_using_the_bom() {
+        let (texto, encoding) =
+            decodifica(b"\xEF\xBB\xBFLei Org\xC3\xA2nica", None, "bom.html").unwrap();
+
+        assert_eq!(texto, "Lei Orgânica");
+        assert_eq!(encoding, UTF_8);
+    }
+
+    #[test]
+    fn should_decode_using_the_meta_charset() {
+        let html = b"<html><head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=ISO-8859-1\"></head>Org\xE2nica";
+        let (texto, encoding) = decodifica(html, None, "meta.html").unwrap();
+
+        assert!(texto.ends_with("Orgânica"));
+        assert_eq!(encoding, WINDOWS_1252);
+
+        let (texto, encoding) =
+            decodifica(b"<meta charset='utf-8'>Org\xC3\xA2nica", None, "meta.html").unwrap();
+        assert!(texto.ends_with("Orgânica"));
+        assert_eq!(encoding, UTF_8);
+    }
+
+    #[test]
+    fn should_guess_the_encoding_of_files_without_declaration() {
+        let bytes =
+            fs::read("resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html")
+                .unwrap();
+        let (texto, encoding) = decodifica(&bytes, None, "lc122.html").unwrap();
+
+        assert_eq!(encoding, WINDOWS_1252);
+        assert!(texto.contains("LEI COMPLEMENTAR Nº 122"));
+    }
+
+    #[test]
+    fn should_use_the_forced_encoding() {
+        let (texto, encoding) = decodifica(
+            b"<meta charset=\"utf-8\">Org\xE2nica",
+            Encoding::for_label(b"latin1"),
+            "latin1.html",
+        )
+        .unwrap();
+
+        assert!(texto.ends_with("Orgânica"));
+        assert_eq!(encoding, WINDOWS_1252);
+    }
+
+    #[test]
+    fn should_return_decoding_error_when_bytes_are_malformed() {
+        let erro = decodifica(b"DISP\xD5E SOBRE", Some(UTF_8), "decreto.html").unwrap_err();
+
+        assert_eq!(
+            &format!("{}", erro),
+            "Arquivo decreto.html não pôde ser decodificado como UTF-8"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::error::Error;
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+const EXTENSOES: [&str; 4] = [".zip", ".tar", ".tar.gz", ".tgz"];
+
+// um arquivo HTML lido de dentro do arquivo compactado, com o caminho como se ele tivesse sido
+// extraído em uma pasta com o nome do arquivo compactado
+pub struct Entrada {
+    pub caminho: String,
+    pub categorias: Vec<String>,
+    pub conteudo: Result<Vec<u8>, Error>,
+}
+
+pub fn e_compactado(caminho: &str) -> bool {
+    let caminho = caminho.to_lowercase();
+    EXTENSOES.iter().any(|extensao| caminho.ends_with(extensao))
+}
+
+// as entradas são lidas em sequência e entregues uma a uma, sem extrair nada para o disco
+pub fn percorre(caminho: &str, visita: &mut dyn FnMut(Entrada)) -> Result<(), Error> {
+    let arquivo = File::open(caminho).map_err(|erro| Error::Io(caminho.to_string(), erro))?;
+    let leitor = BufReader::new(arquivo);
+    let minusculo = caminho.to_lowercase();
+    if minusculo.ends_with(".zip") {
+        percorre_zip(leitor, caminho, visita)
+    } else if minusculo.ends_with(".tar") {
+        percorre_tar(leitor, caminho, visita)
+    } else {
+        percorre_tar(GzDecoder::new(leitor), caminho, visita)
+    }
+}
+
+fn percorre_zip(
+    leitor: BufReader<File>,
+    caminho: &str,
+    visita: &mut dyn FnMut(Entrada),
+) -> Result<(), Error> {
+    let mut zip =
+        zip::ZipArchive::new(leitor).map_err(|erro| Error::Zip(caminho.to_string(), erro))?;
+    for indice in 0..zip.len() {
+        let mut arquivo = zip
+            .by_index(indice)
+            .map_err(|erro| Error::Zip(caminho.to_string(), erro))?;
+        if arquivo.is_dir() {
+            continue;
+        }
+        if let Some((caminho_da_entrada, categorias)) = descreve(caminho, arquivo.name()) {
+            let mut conteudo = Vec::new();
+            let conteudo = arquivo
+                .read_to_end(&mut conteudo)
+                .map(|_| conteudo)
+                .map_err(|erro| Error::Io(caminho_da_entrada.clone(), erro));
+            visita(Entrada {
+                caminho: caminho_da_entrada,
+                categorias,
+                conteudo,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn percorre_tar(
+    leitor: impl Read,
+    caminho: &str,
+    visita: &mut dyn FnMut(Entrada),
+) -> Result<(), Error> {
+    let erro_de_leitura = |erro: io::Error| Error::Io(caminho.to_string(), erro);
+    let mut tar = tar::Archive::new(leitor);
+    for arquivo in tar.entries().map_err(erro_de_leitura)? {
+        let mut arquivo = arquivo.map_err(erro_de_leitura)?;
+        if !arquivo.header().entry_type().is_file() {
+            continue;
+        }
+        let nome = arquivo
+            .path()
+            .map_err(erro_de_leitura)?
+            .to_string_lossy()
+            .to_string();
+        if let Some((caminho_da_entrada, categorias)) = descreve(caminho, &nome) {
+            // num tar a leitura é sequencial, então um erro aqui impede a leitura do resto
+            let mut conteudo = Vec::new();
+            arquivo
+                .read_to_end(&mut conteudo)
+                .map_err(|erro| Error::Io(caminho_da_entrada.clone(), erro))?;
+            visita(Entrada {
+                caminho: caminho_da_entrada,
+                categorias,
+                conteudo: Ok(conteudo),
+            });
+        }
+    }
+    Ok(())
+}
+
+// as pastas dentro do arquivo compactado viram as categorias, como na leitura de um diretório
+fn descreve(caminho: &str, nome: &str) -> Option<(String, Vec<String>)> {
+    let componentes = nome
+        .split('/')
+        .filter(|componente| !componente.is_empty() && *componente != ".")
+        .collect::<Vec<&str>>();
+    let (arquivo, pastas) = componentes.split_last()?;
+    if !arquivo.ends_with(".html") || componentes.iter().any(|c| c.starts_with('.')) {
+        return None;
+    }
+    Some((
+        Path::new(caminho)
+            .join(componentes.join("/"))
+            .to_string_lossy()
+            .to_string(),
+        pastas.iter().map(ToString::to_string).collect(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::compactado::{descreve, e_compactado, percorre};
+
+    #[test]
+    fn should_recognize_archive_extensions() {
+        assert!(e_compactado("dump.zip"));
+        assert!(e_compactado("dump.TAR.GZ"));
+        assert!(e_compactado("dump.tgz"));
+        assert!(!e_compactado("dump/"));
+        assert!(!e_compactado("dump.gz"));
+    }
+
+    #[test]
+    fn should_use_folders_inside_the_archive_as_categorias() {
+        assert_eq!(
+            descreve("dump.zip", "./decretos/1995/Decreto-5907-1995.html"),
+            Some((
+                "dump.zip/decretos/1995/Decreto-5907-1995.html".to_string(),
+                vec!["decretos".to_string(), "1995".to_string()]
+            ))
+        );
+        assert_eq!(descreve("dump.zip", "decretos/leia-me.txt"), None);
+        assert_eq!(descreve("dump.zip", ".ocultos/Decreto-1-1984.html"), None);
+    }
+
+    #[test]
+    fn should_read_the_same_entries_from_zip_and_tar_gz() {
+        let entradas = |caminho: &str| {
+            let mut entradas = Vec::new();
+            percorre(caminho, &mut |entrada| {
+                entradas.push((
+                    entrada.caminho.replacen(caminho, "", 1),
+                    entrada.categorias,
+                    entrada.conteudo.unwrap().len(),
+                ));
+            })
+            .unwrap();
+            entradas.sort();
+            entradas
+        };
+
+        let zip = entradas("resources/integration_tests/leis.zip");
+
+        assert_eq!(zip.len(), 3);
+        assert_eq!(zip, entradas("resources/integration_tests/leis.tar.gz"));
+    }
+}
@@ -1,9 +1,35 @@
 use failure::Fail;
+use std::io;
 
+/// Erros do parse e da exportação das leis. O primeiro campo de cada variante é o caminho do
+/// arquivo envolvido.
 #[derive(Fail, Debug)]
+#[non_exhaustive]
 pub enum Error {
+    /// Um trecho obrigatório, como o título da lei, não foi encontrado no HTML.
     #[fail(display = "{} não encontrado no arquivo {}", _0, _1)]
     PatternNotFound(String, String),
+    /// Falha ao ler ou gravar um arquivo.
+    #[fail(display = "Erro ao acessar o arquivo {}: {}", _0, _1)]
+    Io(String, #[cause] io::Error),
+    /// O arquivo não está na codificação detectada ou forçada.
+    #[fail(display = "Arquivo {} não pôde ser decodificado como {}", _0, _1)]
+    Decodificacao(String, String),
+    /// Falha ao percorrer a pasta das leis.
+    #[fail(display = "Erro ao percorrer o diretório {}: {}", _0, _1)]
+    Diretorio(String, #[cause] walkdir::Error),
+    /// Falha ao gravar o banco `SQLite`.
+    #[fail(display = "Erro ao gravar o banco de dados {}: {}", _0, _1)]
+    Sqlite(String, #[cause] rusqlite::Error),
+    /// Falha ao gravar os arquivos Parquet.
+    #[fail(display = "Erro ao gravar o arquivo Parquet {}: {}", _0, _1)]
+    Parquet(String, #[cause] parquet::errors::ParquetError),
+    /// O arquivo `.zip` está corrompido ou usa um formato não suportado.
+    #[fail(display = "Erro ao ler o arquivo ZIP {}: {}", _0, _1)]
+    Zip(String, #[cause] zip::result::ZipError),
+    /// Não foi possível escutar no endereço do servidor da API. O primeiro campo é o endereço.
+    #[fail(display = "Erro ao iniciar o servidor em {}: {}", _0, _1)]
+    Servidor(String, #[cause] io::Error),
 }
 
 pub trait CapturedOkOrUnexpected<T> {
@@ -0,0 +1,307 @@
+use crate::parser::clean_html_to_text;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref ARTIGO_ANCORA_REGEX: Regex =
+        Regex::new(r#"<a\s+name=["']?artigo_[^"'>]*["']?\s*>"#).unwrap();
+    static ref SPAN_REGEX: Regex = Regex::new(r"</?span\b[^>]*>").unwrap();
+    static ref DOWNLOAD_REGEX: Regex = Regex::new(r"<a[^>]*btn-default[^>]*>.*?</a>").unwrap();
+    static ref ARTIGO_REGEX: Regex =
+        Regex::new(r"^Art\.?\s*(?P<numero>\d+(?:-[A-Z])?)\s*(?P<ordinal>[º°])?\.?\s*").unwrap();
+    static ref PARAGRAFO_REGEX: Regex = Regex::new(
+        r"(?i)^(?:§\s*(?P<numero>\d+)\s*(?P<ordinal>[º°])?|(?P<unico>par[áa]grafo\s+[úu]nico))\s*[.:-]?\s*"
+    )
+    .unwrap();
+    static ref INCISO_REGEX: Regex = Regex::new(r"^(?P<rotulo>[IVXLCDM]+)\s*[-–—]\s*").unwrap();
+    static ref ALINEA_REGEX: Regex = Regex::new(r"^(?P<rotulo>[a-z])\)\s*").unwrap();
+    static ref ITEM_REGEX: Regex = Regex::new(r"^(?P<rotulo>\d+)\s*[-–—.)]\s+").unwrap();
+}
+
+const TITULO_INICIO: &str = r#"<span class="titulo""#;
+
+/// Nível de um dispositivo na hierarquia dos artigos.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TipoDispositivo {
+    Artigo,
+    Paragrafo,
+    Inciso,
+    Alinea,
+    Item,
+}
+
+/// Um artigo, parágrafo, inciso, alínea ou item da lei.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Dispositivo {
+    /// Nível do dispositivo.
+    pub tipo: TipoDispositivo,
+    /// Rótulo como aparece no texto, como `Art. 1º`, `§ 2º` ou `III`.
+    pub rotulo: String,
+    /// Texto do dispositivo, sem o rótulo e sem os dispositivos filhos.
+    pub texto: String,
+    /// Dispositivos subordinados, como os parágrafos de um artigo.
+    pub filhos: Vec<Dispositivo>,
+}
+
+impl Dispositivo {
+    fn new(tipo: TipoDispositivo, rotulo: String, texto: &str) -> Dispositivo {
+        Dispositivo {
+            tipo,
+            rotulo,
+            texto: texto.trim().to_string(),
+            filhos: Vec::new(),
+        }
+    }
+
+    fn adiciona(&mut self, dispositivo: Dispositivo) {
+        if let Some(ultimo) = self
+            .filhos
+            .last_mut()
+            .filter(|ultimo| ultimo.tipo < dispositivo.tipo)
+        {
+            ultimo.adiciona(dispositivo);
+            return;
+        }
+        self.filhos.push(dispositivo);
+    }
+
+    fn continua(&mut self, linha: &str) {
+        if let Some(ultimo) = self.filhos.last_mut() {
+            ultimo.continua(linha);
+        } else if self.texto.is_empty() {
+            self.texto = linha.to_string();
+        } else {
+            self.texto.push('\n');
+            self.texto.push_str(linha);
+        }
+    }
+}
+
+pub fn parse_artigos(texto_html: &str) -> Vec<Dispositivo> {
+    let texto_html = DOWNLOAD_REGEX.replace_all(texto_html, "");
+    let texto_html = remove_titulos(&texto_html);
+
+    let mut limites = ARTIGO_ANCORA_REGEX
+        .find_iter(&texto_html)
+        .map(|ancora| ancora.start())
+        .collect::<Vec<usize>>();
+    limites.push(texto_html.len());
+
+    limites
+        .windows(2)
+        .filter_map(|limite| parse_artigo(&clean_html_to_text(&texto_html[limite[0]..limite[1]])))
+        .collect()
+}
+
+fn parse_artigo(texto: &str) -> Option<Dispositivo> {
+    let texto = texto.trim();
+    let captures = ARTIGO_REGEX.captures(texto)?;
+    let rotulo = format!(
+        "Art. {}{}",
+        &captures["numero"],
+        captures
+            .name("ordinal")
+            .map_or("", |ordinal| ordinal.as_str())
+    );
+
+    let mut linhas = texto[captures[0].len()..]
+        .lines()
+        .map(str::trim)
+        .filter(|linha| !linha.is_empty());
+    let mut artigo = Dispositivo::new(
+        TipoDispositivo::Artigo,
+        rotulo,
+        linhas.next().unwrap_or_default(),
+    );
+
+    let mut dentro_de_citacao = abre_ou_fecha_citacao(&artigo.texto);
+    for linha in linhas {
+        match parse_dispositivo(linha) {
+            Some(dispositivo) if !dentro_de_citacao => artigo.adiciona(dispositivo),
+            _ => artigo.continua(linha),
+        }
+        if abre_ou_fecha_citacao(linha) {
+            dentro_de_citacao = !dentro_de_citacao;
+        }
+    }
+
+    Some(artigo)
+}
+
+fn parse_dispositivo(linha: &str) -> Option<Dispositivo> {
+    if let Some(captures) = PARAGRAFO_REGEX.captures(linha) {
+        let rotulo = match captures.name("numero") {
+            Some(numero) => format!(
+                "§ {}{}",
+                numero.as_str(),
+                captures
+                    .name("ordinal")
+                    .map_or("", |ordinal| ordinal.as_str())
+            ),
+            None => "Parágrafo único".to_string(),
+        };
+        return Some(Dispositivo::new(
+            TipoDispositivo::Paragrafo,
+            rotulo,
+            &linha[captures[0].len()..],
+        ));
+    }
+
+    [
+        (&*INCISO_REGEX, TipoDispositivo::Inciso),
+        (&*ALINEA_REGEX, TipoDispositivo::Alinea),
+        (&*ITEM_REGEX, TipoDispositivo::Item),
+    ]
+    .iter()
+    .find_map(|(regex, tipo)| {
+        regex.captures(linha).map(|captures| {
+            Dispositivo::new(
+                *tipo,
+                captures["rotulo"].to_string(),
+                &linha[captures[0].len()..],
+            )
+        })
+    })
+}
+
+fn abre_ou_fecha_citacao(linha: &str) -> bool {
+    linha
+        .chars()
+        .filter(|c| matches!(c, '"' | '“' | '”'))
+        .count()
+        % 2
+        == 1
+}
+
+fn remove_titulos(html: &str) -> String {
+    let mut resultado = String::with_capacity(html.len());
+    let mut restante = html;
+    while let Some(inicio) = restante.find(TITULO_INICIO) {
+        resultado.push_str(&restante[..inicio]);
+        restante = &restante[inicio..];
+        restante = &restante[fim_do_span(restante)..];
+    }
+    resultado.push_str(restante);
+    resultado
+}
+
+fn fim_do_span(html: &str) -> usize {
+    let mut profundidade = 0;
+    for tag in SPAN_REGEX.find_iter(html) {
+        if tag.as_str().starts_with("</") {
+            profundidade -= 1;
+            if profundidade == 0 {
+                return tag.end();
+            }
+        } else {
+            profundidade += 1;
+        }
+    }
+    html.len()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::estrutura::{parse_artigos, Dispositivo, TipoDispositivo};
+
+    fn dispositivo(
+        tipo: TipoDispositivo,
+        rotulo: &str,
+        texto: &str,
+        filhos: Vec<Dispositivo>,
+    ) -> Dispositivo {
+        Dispositivo {
+            tipo,
+            rotulo: rotulo.to_string(),
+            texto: texto.to_string(),
+            filhos,
+        }
+    }
+
+    #[test]
+    fn should_parse_artigos_with_paragrafos_incisos_alineas_and_itens() {
+        let html = "Preâmbulo<br><br>\
+            <a name=\"artigo_1\"><span class=\"label label-danger\">Art. 1º</span></a> Caput:<br><br>\
+            I - primeiro inciso;<br><br>\
+            a) primeira alínea;<br>\
+            1. primeiro item;<br>\
+            II - segundo inciso.<br><br>\
+            § 1º Primeiro parágrafo:<br><br>\
+            I - inciso do parágrafo.<br><br>\
+            <span class=\"titulo\"><br> Capítulo <span class=\"titulo\"> II<br>DISPOSIÇÕES FINAIS<br></span></span><br><br>\
+            <a name=\"artigo_2\"><span class=\"label label-danger\">Art. 2.</span></a> Segundo artigo.<br><br>\
+            Parágrafo único. Único parágrafo.";
+
+        assert_eq!(
+            parse_artigos(html),
+            vec![
+                dispositivo(
+                    TipoDispositivo::Artigo,
+                    "Art. 1º",
+                    "Caput:",
+                    vec![
+                        dispositivo(
+                            TipoDispositivo::Inciso,
+                            "I",
+                            "primeiro inciso;",
+                            vec![dispositivo(
+                                TipoDispositivo::Alinea,
+                                "a",
+                                "primeira alínea;",
+                                vec![dispositivo(
+                                    TipoDispositivo::Item,
+                                    "1",
+                                    "primeiro item;",
+                                    vec![]
+                                )]
+                            )]
+                        ),
+                        dispositivo(TipoDispositivo::Inciso, "II", "segundo inciso.", vec![]),
+                        dispositivo(
+                            TipoDispositivo::Paragrafo,
+                            "§ 1º",
+                            "Primeiro parágrafo:",
+                            vec![dispositivo(
+                                TipoDispositivo::Inciso,
+                                "I",
+                                "inciso do parágrafo.",
+                                vec![]
+                            )]
+                        ),
+                    ]
+                ),
+                dispositivo(
+                    TipoDispositivo::Artigo,
+                    "Art. 2",
+                    "Segundo artigo.",
+                    vec![dispositivo(
+                        TipoDispositivo::Paragrafo,
+                        "Parágrafo único",
+                        "Único parágrafo.",
+                        vec![]
+                    )]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_keep_quoted_dispositivos_in_the_artigo_text() {
+        let html = "<a name=\"artigo_1\"><span>Art. 1º</span></a> Passa a vigorar:<br><br>\
+            &quot;Art. 48. Nova redação:<br><br>\
+            I - inciso citado.&quot;";
+
+        assert_eq!(
+            parse_artigos(html),
+            vec![dispositivo(
+                TipoDispositivo::Artigo,
+                "Art. 1º",
+                "Passa a vigorar:\n\"Art. 48. Nova redação:\nI - inciso citado.\"",
+                vec![]
+            )]
+        );
+    }
+}
@@ -0,0 +1,492 @@
+//! Gravação das leis parseadas em JSON, JSON Lines, CSV, `SQLite`, Parquet, `LexML`, Akoma Ntoso
+//! e Markdown.
+
+use crate::error::Error;
+use crate::exportacao_akn::{escreve_akn, frbr_uri, nome_do_documento};
+use crate::exportacao_csv::{escreve_leis_csv, escreve_pastas_csv};
+use crate::exportacao_lexml::{escreve_lexml, nome_do_arquivo, urn, LOCALIDADE_PADRAO};
+use crate::exportacao_markdown::{caminho_do_arquivo, escreve_markdown};
+use crate::exportacao_parquet::escreve_leis_parquet;
+use crate::exportacao_sqlite::escreve_leis_sqlite;
+use crate::parser::Lei;
+use crate::parser_executor::Folder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+
+pub use crate::exportacao_csv::Coluna;
+
+// limita quantas leis parseadas ficam em memória esperando a gravação, segurando o parse quando
+// a escrita é mais lenta
+const LEIS_EM_MEMORIA: usize = 64;
+
+/// Formato em que as leis são gravadas por [`escreve_leis`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Formato {
+    /// Um array JSON com todas as leis.
+    Json,
+    /// Uma lei por linha, que pode ser gravada à medida que as leis são parseadas.
+    Jsonl,
+    /// Uma planilha com as [`Coluna`]s escolhidas.
+    Csv,
+    /// Um banco `SQLite` com tabelas normalizadas e busca textual.
+    Sqlite,
+    /// Arquivos Parquet com as leis, os artigos e as referências.
+    Parquet,
+    /// Uma pasta com um documento `LexML` por lei.
+    Lexml,
+    /// Uma pasta com um documento Akoma Ntoso por lei.
+    Akn,
+    /// Uma pasta com um arquivo Markdown por lei, organizados por categoria.
+    Markdown,
+}
+
+impl Formato {
+    /// Nomes aceitos por [`Formato::from_str`](std::str::FromStr::from_str).
+    pub const VALORES: &'static [&'static str] = &[
+        "json",
+        "jsonl",
+        "ndjson",
+        "csv",
+        "sqlite",
+        "parquet",
+        "lexml",
+        "akn",
+        "akomantoso",
+        "markdown",
+        "md",
+    ];
+
+    /// Arquivo ou pasta gravado quando nenhum caminho é informado.
+    #[must_use]
+    pub fn arquivo_padrao(self) -> &'static str {
+        match self {
+            Formato::Json => "leis.json",
+            Formato::Jsonl => "leis.jsonl",
+            Formato::Csv => "leis.csv",
+            Formato::Sqlite => "leis.db",
+            Formato::Parquet => "leis.parquet",
+            Formato::Lexml => "lexml",
+            Formato::Akn => "akn",
+            Formato::Markdown => "markdown",
+        }
+    }
+
+    /// Indica se as leis podem ser gravadas por [`escreve_em_fluxo`], uma a uma.
+    #[must_use]
+    pub fn em_fluxo(self) -> bool {
+        self == Formato::Jsonl
+    }
+}
+
+impl FromStr for Formato {
+    type Err = String;
+
+    fn from_str(valor: &str) -> Result<Self, Self::Err> {
+        match valor.to_lowercase().as_str() {
+            "json" => Ok(Formato::Json),
+            "jsonl" | "ndjson" => Ok(Formato::Jsonl),
+            "csv" => Ok(Formato::Csv),
+            "sqlite" => Ok(Formato::Sqlite),
+            "parquet" => Ok(Formato::Parquet),
+            "lexml" => Ok(Formato::Lexml),
+            "akn" | "akomantoso" => Ok(Formato::Akn),
+            "markdown" | "md" => Ok(Formato::Markdown),
+            _ => Err(format!("Formato desconhecido: {}", valor)),
+        }
+    }
+}
+
+/// Opções dos formatos que as usam, com padrões em [`OpcoesDeExportacao::default`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpcoesDeExportacao {
+    /// Colunas do CSV.
+    pub colunas: Vec<Coluna>,
+    /// Quantidade máxima de caracteres das colunas de texto do CSV.
+    pub limite_do_texto: Option<usize>,
+    /// Grava o BOM do UTF-8 no início do CSV, para o Excel.
+    pub bom: bool,
+    /// Localidade das URNs do `LexML` e do Akoma Ntoso, como `br;bahia;feira.de.santana`.
+    pub localidade: String,
+    /// Número do publicador cadastrado no `LexML`, gravado no `Item` de cada documento.
+    pub id_publicador: u32,
+}
+
+impl Default for OpcoesDeExportacao {
+    fn default() -> Self {
+        OpcoesDeExportacao {
+            colunas: Coluna::PADRAO.to_vec(),
+            limite_do_texto: None,
+            bom: false,
+            localidade: LOCALIDADE_PADRAO.to_string(),
+            id_publicador: 0,
+        }
+    }
+}
+
+/// Grava as leis no caminho indicado, que é uma pasta nos formatos com um arquivo por lei.
+/// Com `-` como caminho, os formatos de um único arquivo texto são gravados na saída padrão.
+///
+/// Retorna a quantidade de leis gravadas: no `LexML` e no Akoma Ntoso, as leis sem tipo, número
+/// e data identificados não têm identificador e ficam de fora.
+///
+/// # Errors
+///
+/// Retorna erro se algum arquivo não puder ser gravado ou se o formato não puder ser gravado
+/// na saída padrão.
+pub fn escreve_leis(
+    leis: &[Lei],
+    formato: Formato,
+    caminho: &Path,
+    opcoes: &OpcoesDeExportacao,
+) -> Result<usize, Error> {
+    match formato {
+        Formato::Json | Formato::Jsonl | Formato::Csv => {
+            escreve_arquivo(leis, formato, caminho, opcoes).map(|()| leis.len())
+        }
+        Formato::Sqlite => escreve_banco(leis, caminho).map(|()| leis.len()),
+        Formato::Parquet => escreve_parquet(leis, caminho).map(|()| leis.len()),
+        Formato::Lexml => escreve_documentos(
+            leis,
+            caminho,
+            "o LexML",
+            |lei| urn(lei, &opcoes.localidade),
+            nome_do_arquivo,
+            |saida, lei, urn| escreve_lexml(saida, lei, urn, opcoes.id_publicador),
+        ),
+        Formato::Akn => escreve_documentos(
+            leis,
+            caminho,
+            "o Akoma Ntoso",
+            frbr_uri,
+            nome_do_documento,
+            |saida, lei, uri| escreve_akn(saida, lei, uri, &opcoes.localidade),
+        ),
+        Formato::Markdown => escreve_pastas_markdown(leis, caminho).map(|()| leis.len()),
+    }
+}
+
+/// Grava em CSV o resumo por pasta devolvido por [`parse_on_directory`](crate::parse_on_directory).
+///
+/// # Errors
+///
+/// Retorna [`Error::Io`] se o arquivo não puder ser gravado.
+pub fn escreve_resumo_das_pastas(
+    directories: &HashMap<String, Folder>,
+    caminho: &Path,
+    opcoes: &OpcoesDeExportacao,
+) -> Result<(), Error> {
+    let mut saida = abre_saida(caminho)?;
+    escreve_pastas_csv(&mut saida, directories, opcoes.bom)
+        .and_then(|_| saida.flush())
+        .map_err(|erro| erro_de_io(caminho, erro))
+}
+
+/// Grava em JSON Lines, em uma única thread, as leis enviadas ao canal devolvido, na ordem em
+/// que chegam. A thread termina com a quantidade de leis gravadas quando o canal é fechado.
+///
+/// O canal guarda poucas leis: o envio espera enquanto a gravação não alcança o parse.
+///
+/// # Errors
+///
+/// Retorna [`Error::Io`] se o arquivo não puder ser criado.
+pub fn escreve_em_fluxo(
+    caminho: &Path,
+) -> Result<(SyncSender<Lei>, JoinHandle<Result<usize, Error>>), Error> {
+    let mut saida = abre_saida(caminho)?;
+    let caminho = caminho.to_path_buf();
+    let (sender, receiver) = mpsc::sync_channel::<Lei>(LEIS_EM_MEMORIA);
+
+    let escritor = thread::spawn(move || {
+        let mut total = 0;
+        for lei in receiver {
+            escreve_linha(&mut saida, &lei, &caminho)?;
+            total += 1;
+        }
+        saida.flush().map_err(|erro| erro_de_io(&caminho, erro))?;
+        Ok(total)
+    });
+
+    Ok((sender, escritor))
+}
+
+/// Indica se o caminho é `-`, que representa a saída padrão.
+#[must_use]
+pub fn e_saida_padrao(caminho: &Path) -> bool {
+    caminho == Path::new("-")
+}
+
+/// Caminho de um arquivo gravado ao lado do principal, como `leis-pastas.csv` ao lado de
+/// `leis.csv` ou `leis-artigos.parquet` ao lado de `leis.parquet`.
+#[must_use]
+pub fn caminho_relacionado(caminho: &Path, sufixo: &str) -> PathBuf {
+    let nome = caminho
+        .file_stem()
+        .map_or_else(|| "leis".into(), |nome| nome.to_string_lossy());
+    let extensao = caminho.extension().map_or_else(String::new, |extensao| {
+        format!(".{}", extensao.to_string_lossy())
+    });
+    caminho.with_file_name(format!("{}-{}{}", nome, sufixo, extensao))
+}
+
+// guarda os enums com os mesmos nomes usados no JSON
+pub(crate) fn nome_serializado<T: Serialize>(valor: &T) -> Option<String> {
+    serde_json::to_value(valor)
+        .ok()
+        .and_then(|valor| valor.as_str().map(str::to_string))
+}
+
+/// Lê as leis de um arquivo JSON, ou JSON Lines se a extensão for `.jsonl` ou `.ndjson`.
+///
+/// # Errors
+///
+/// Retorna [`Error::Io`] se o arquivo não puder ser lido ou não contiver leis válidas.
+pub fn le_leis(caminho: &Path) -> Result<Vec<Lei>, Error> {
+    let arquivo = File::open(caminho).map_err(|erro| erro_de_io(caminho, erro))?;
+    let extensao = caminho.extension().and_then(|extensao| extensao.to_str());
+    if let Some("jsonl") | Some("ndjson") = extensao {
+        return serde_json::Deserializer::from_reader(BufReader::new(arquivo))
+            .into_iter()
+            .collect::<Result<Vec<Lei>, _>>()
+            .map_err(|erro| erro_de_io(caminho, erro.into()));
+    }
+    serde_json::from_reader(BufReader::new(arquivo))
+        .map_err(|erro| erro_de_io(caminho, erro.into()))
+}
+
+fn escreve_arquivo(
+    leis: &[Lei],
+    formato: Formato,
+    caminho: &Path,
+    opcoes: &OpcoesDeExportacao,
+) -> Result<(), Error> {
+    let mut saida = abre_saida(caminho)?;
+    match formato {
+        Formato::Jsonl => {
+            for lei in leis {
+                escreve_linha(&mut saida, lei, caminho)?;
+            }
+        }
+        Formato::Csv => escreve_leis_csv(
+            &mut saida,
+            leis,
+            &opcoes.colunas,
+            opcoes.limite_do_texto,
+            opcoes.bom,
+        )
+        .map_err(|erro| erro_de_io(caminho, erro))?,
+        _ => serde_json::to_writer_pretty(&mut saida, leis)
+            .map_err(|erro| erro_de_io(caminho, erro.into()))?,
+    }
+    saida.flush().map_err(|erro| erro_de_io(caminho, erro))
+}
+
+// o banco é recriado do zero a cada exportação
+fn escreve_banco(leis: &[Lei], caminho: &Path) -> Result<(), Error> {
+    exige_arquivo(caminho, "o banco SQLite")?;
+    if caminho.exists() {
+        fs::remove_file(caminho).map_err(|erro| erro_de_io(caminho, erro))?;
+    }
+    escreve_leis_sqlite(leis, caminho)
+        .map_err(|erro| Error::Sqlite(caminho.display().to_string(), erro))
+}
+
+// as tabelas de artigos e referências ficam em arquivos ao lado do das leis
+fn escreve_parquet(leis: &[Lei], caminho: &Path) -> Result<(), Error> {
+    exige_arquivo(caminho, "o arquivo Parquet")?;
+    escreve_leis_parquet(
+        leis,
+        caminho,
+        &caminho_relacionado(caminho, "artigos"),
+        &caminho_relacionado(caminho, "referencias"),
+    )
+    .map_err(|erro| Error::Parquet(caminho.display().to_string(), erro))
+}
+
+// um documento por lei, com o nome derivado do identificador; leis sem identificador ficam de
+// fora e não entram na quantidade devolvida
+fn escreve_documentos(
+    leis: &[Lei],
+    diretorio: &Path,
+    descricao: &str,
+    identificador: impl Fn(&Lei) -> Option<String>,
+    nome: impl Fn(&str) -> String,
+    escreve: impl Fn(&mut dyn Write, &Lei, &str) -> io::Result<()>,
+) -> Result<usize, Error> {
+    exige_arquivo(diretorio, descricao)?;
+    fs::create_dir_all(diretorio).map_err(|erro| erro_de_io(diretorio, erro))?;
+    let mut gravadas = 0;
+    for lei in leis {
+        if let Some(identificador) = identificador(lei) {
+            let caminho = diretorio.join(nome(&identificador));
+            let mut saida = abre_saida(&caminho)?;
+            escreve(&mut saida, lei, &identificador)
+                .and_then(|_| saida.flush())
+                .map_err(|erro| erro_de_io(&caminho, erro))?;
+            gravadas += 1;
+        }
+    }
+    Ok(gravadas)
+}
+
+// um arquivo por lei, dentro de uma pasta por categoria
+fn escreve_pastas_markdown(leis: &[Lei], diretorio: &Path) -> Result<(), Error> {
+    exige_arquivo(diretorio, "o Markdown")?;
+    for lei in leis {
+        let caminho = diretorio.join(caminho_do_arquivo(lei));
+        if let Some(pasta) = caminho.parent() {
+            fs::create_dir_all(pasta).map_err(|erro| erro_de_io(pasta, erro))?;
+        }
+        let mut saida = abre_saida(&caminho)?;
+        escreve_markdown(&mut saida, lei)
+            .and_then(|_| saida.flush())
+            .map_err(|erro| erro_de_io(&caminho, erro))?;
+    }
+    Ok(())
+}
+
+fn exige_arquivo(caminho: &Path, descricao: &str) -> Result<(), Error> {
+    if e_saida_padrao(caminho) {
+        return Err(erro_de_io(
+            caminho,
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} não pode ser gravado na saída padrão", descricao),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn abre_saida(caminho: &Path) -> Result<Box<dyn Write + Send>, Error> {
+    if e_saida_padrao(caminho) {
+        return Ok(Box::new(BufWriter::new(io::stdout())));
+    }
+    let arquivo = File::create(caminho).map_err(|erro| erro_de_io(caminho, erro))?;
+    Ok(Box::new(BufWriter::new(arquivo)))
+}
+
+fn escreve_linha(saida: &mut dyn Write, lei: &Lei, caminho: &Path) -> Result<(), Error> {
+    serde_json::to_writer(&mut *saida, lei).map_err(|erro| erro_de_io(caminho, erro.into()))?;
+    saida
+        .write_all(b"\n")
+        .map_err(|erro| erro_de_io(caminho, erro))
+}
+
+pub(crate) fn erro_de_io(caminho: &Path, erro: io::Error) -> Error {
+    Error::Io(caminho.display().to_string(), erro)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exportacao::{
+        caminho_relacionado, escreve_em_fluxo, escreve_leis, le_leis, Formato, OpcoesDeExportacao,
+    };
+    use crate::identificacao::Identificacao;
+    use crate::parser::{parse_html_to_lei, Lei};
+    use assert_fs::TempDir;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::slice;
+
+    fn decreto() -> Lei {
+        parse_html_to_lei(
+            "resources/unit_tests/LeisMunicipais-com-br-Decreto-1-1984.html",
+            vec!["test".to_string()],
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_write_and_read_back_leis_as_json() {
+        let lei = decreto();
+        let temp = TempDir::new().unwrap();
+        let caminho = temp.path().join("leis.json");
+
+        escreve_leis(
+            slice::from_ref(&lei),
+            Formato::Json,
+            &caminho,
+            &OpcoesDeExportacao::default(),
+        )
+        .unwrap();
+
+        assert_eq!(le_leis(&caminho).unwrap(), vec![lei]);
+    }
+
+    #[test]
+    fn should_write_one_lei_per_line_as_jsonl() {
+        let temp = TempDir::new().unwrap();
+        let caminho = temp.path().join("leis.jsonl");
+
+        let (sender, escritor) = escreve_em_fluxo(&caminho).unwrap();
+        sender.send(decreto()).unwrap();
+        sender.send(decreto()).unwrap();
+        drop(sender);
+
+        assert_eq!(escritor.join().unwrap().unwrap(), 2);
+        let conteudo = fs::read_to_string(&caminho).unwrap();
+        let linhas = conteudo.lines().collect::<Vec<&str>>();
+        assert_eq!(linhas.len(), 2);
+        assert_eq!(serde_json::from_str::<Lei>(linhas[0]).unwrap(), decreto());
+        assert_eq!(le_leis(&caminho).unwrap(), vec![decreto(), decreto()]);
+    }
+
+    #[test]
+    fn should_count_leis_left_out_for_lack_of_identifier() {
+        let temp = TempDir::new().unwrap();
+        let sem_identificacao = Lei {
+            identificacao: Identificacao::default(),
+            ..decreto()
+        };
+        let leis = vec![decreto(), sem_identificacao];
+
+        for formato in &[Formato::Lexml, Formato::Akn] {
+            let diretorio = temp.path().join(format!("{:?}", formato));
+            let gravadas =
+                escreve_leis(&leis, *formato, &diretorio, &OpcoesDeExportacao::default()).unwrap();
+
+            assert_eq!(gravadas, 1);
+            assert_eq!(fs::read_dir(&diretorio).unwrap().count(), 1);
+        }
+        assert_eq!(
+            escreve_leis(
+                &leis,
+                Formato::Json,
+                &temp.path().join("leis.json"),
+                &OpcoesDeExportacao::default()
+            )
+            .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn should_parse_formato_ignoring_case() {
+        assert_eq!("JSON".parse::<Formato>(), Ok(Formato::Json));
+        assert_eq!("ndjson".parse::<Formato>(), Ok(Formato::Jsonl));
+        assert_eq!(
+            "xml".parse::<Formato>(),
+            Err("Formato desconhecido: xml".to_string())
+        );
+    }
+
+    #[test]
+    fn should_name_related_files_after_the_output() {
+        assert_eq!(
+            caminho_relacionado(Path::new("saida/leis.parquet"), "artigos"),
+            PathBuf::from("saida/leis-artigos.parquet")
+        );
+        assert_eq!(
+            caminho_relacionado(Path::new("leis.csv"), "pastas"),
+            PathBuf::from("leis-pastas.csv")
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use crate::estrutura::{Dispositivo, TipoDispositivo};
+use crate::exportacao_lexml::{nome_do_tipo, urn};
+use crate::identificacao::data_iso;
+use crate::parser::Lei;
+use crate::xml::{escapa, DECLARACAO};
+use regex::Regex;
+use std::io::{self, Write};
+
+lazy_static! {
+    static ref INICIO_DO_CORPO_REGEX: Regex =
+        Regex::new(r"(?i)^(?:Art\.?\s*\d|Cap[íi]tulo|T[íi]tulo|Se[çc][ãa]o)").unwrap();
+    static ref FECHO_REGEX: Regex =
+        Regex::new(r"(?i)(?P<data>\d{1,2}\s*[º°]?\s+de\s+\p{L}+\s+de\s+\d{4})\.?\s*$").unwrap();
+}
+
+const NAMESPACE: &str = "http://docs.oasis-open.org/legaldocml/ns/akn/3.0";
+
+pub fn frbr_uri(lei: &Lei) -> Option<String> {
+    let identificacao = &lei.identificacao;
+    Some(format!(
+        "/akn/br/act/{}/{}/{}",
+        nome_do_tipo(identificacao.tipo?),
+        identificacao.data.as_ref()?,
+        identificacao.numero?
+    ))
+}
+
+pub fn nome_do_documento(uri: &str) -> String {
+    format!("{}.xml", uri.trim_start_matches("/akn/").replace('/', "_"))
+}
+
+pub fn escreve_akn(
+    saida: &mut dyn Write,
+    lei: &Lei,
+    uri: &str,
+    localidade: &str,
+) -> io::Result<()> {
+    let (artigos, fecho) = separa_fecho(&lei.artigos);
+
+    writeln!(saida, "{}", DECLARACAO)?;
+    writeln!(saida, r#"<akomaNtoso xmlns="{}">"#, NAMESPACE)?;
+    writeln!(
+        saida,
+        r#"  <act name="{}">"#,
+        lei.identificacao.tipo.map_or("norma", nome_do_tipo)
+    )?;
+    escreve_meta(saida, lei, uri, localidade)?;
+
+    let preambulo = preambulo(&lei.texto);
+    if !preambulo.is_empty() {
+        writeln!(saida, "    <preamble>")?;
+        for linha in preambulo {
+            writeln!(saida, "      <p>{}</p>", escapa(linha))?;
+        }
+        writeln!(saida, "    </preamble>")?;
+    }
+
+    writeln!(saida, "    <body>")?;
+    if artigos.is_empty() {
+        writeln!(
+            saida,
+            r#"      <hcontainer name="texto" eId="hcontainer_1">"#
+        )?;
+        escreve_paragrafos(saida, "content", &lei.texto, 4)?;
+        writeln!(saida, "      </hcontainer>")?;
+    }
+    for (indice, artigo) in (1..).zip(&artigos) {
+        escreve_dispositivo(saida, artigo, None, indice, 3)?;
+    }
+    writeln!(saida, "    </body>")?;
+
+    if let Some((local_e_data, assinaturas)) = fecho.split_first() {
+        writeln!(saida, "    <conclusions>")?;
+        writeln!(saida, "      <p>{}</p>", marca_data(local_e_data))?;
+        for assinatura in assinaturas {
+            writeln!(
+                saida,
+                "      <p><signature>{}</signature></p>",
+                escapa(assinatura)
+            )?;
+        }
+        writeln!(saida, "    </conclusions>")?;
+    }
+
+    if let Some(documento) = &lei.documento {
+        writeln!(saida, "    <attachments>")?;
+        writeln!(saida, "      <attachment>")?;
+        writeln!(
+            saida,
+            r#"        <documentRef href="{}" showAs="Documento"/>"#,
+            escapa(documento)
+        )?;
+        writeln!(saida, "      </attachment>")?;
+        writeln!(saida, "    </attachments>")?;
+    }
+
+    writeln!(saida, "  </act>")?;
+    writeln!(saida, "</akomaNtoso>")
+}
+
+fn escreve_meta(saida: &mut dyn Write, lei: &Lei, uri: &str, localidade: &str) -> io::Result<()> {
+    let identificacao = &lei.identificacao;
+    let data = identificacao.data.as_deref().unwrap_or_default();
+    let uri = escapa(uri);
+
+    writeln!(saida, "    <meta>")?;
+    writeln!(
+        saida,
+        r##"      <identification source="#leismunicipais">"##
+    )?;
+    writeln!(saida, "        <FRBRWork>")?;
+    writeln!(saida, r#"          <FRBRthis value="{}/!main"/>"#, uri)?;
+    writeln!(saida, r#"          <FRBRuri value="{}"/>"#, uri)?;
+    if let Some(urn) = urn(lei, localidade) {
+        writeln!(
+            saida,
+            r#"          <FRBRalias value="{}" name="urn:lex"/>"#,
+            escapa(&urn)
+        )?;
+    }
+    writeln!(
+        saida,
+        r#"          <FRBRdate date="{}" name="assinatura"/>"#,
+        data
+    )?;
+    writeln!(saida, r##"          <FRBRauthor href="#municipio"/>"##)?;
+    writeln!(saida, r#"          <FRBRcountry value="br"/>"#)?;
+    if let Some(numero) = identificacao.numero {
+        writeln!(saida, r#"          <FRBRnumber value="{}"/>"#, numero)?;
+    }
+    writeln!(saida, "        </FRBRWork>")?;
+    writeln!(saida, "        <FRBRExpression>")?;
+    writeln!(saida, r#"          <FRBRthis value="{}/por@/!main"/>"#, uri)?;
+    writeln!(saida, r#"          <FRBRuri value="{}/por@"/>"#, uri)?;
+    writeln!(
+        saida,
+        r#"          <FRBRdate date="{}" name="assinatura"/>"#,
+        data
+    )?;
+    writeln!(saida, r##"          <FRBRauthor href="#municipio"/>"##)?;
+    writeln!(saida, r#"          <FRBRlanguage language="por"/>"#)?;
+    writeln!(saida, "        </FRBRExpression>")?;
+    writeln!(saida, "        <FRBRManifestation>")?;
+    writeln!(
+        saida,
+        r#"          <FRBRthis value="{}/por@/main.xml"/>"#,
+        uri
+    )?;
+    writeln!(saida, r#"          <FRBRuri value="{}/por@.akn"/>"#, uri)?;
+    writeln!(
+        saida,
+        r#"          <FRBRdate date="{}" name="assinatura"/>"#,
+        data
+    )?;
+    writeln!(saida, r##"          <FRBRauthor href="#leismunicipais"/>"##)?;
+    writeln!(saida, "        </FRBRManifestation>")?;
+    writeln!(saida, "      </identification>")?;
+    writeln!(saida, r##"      <references source="#leismunicipais">"##)?;
+    writeln!(
+        saida,
+        r#"        <TLCOrganization eId="leismunicipais" href="/ontology/organization/br/leismunicipais" showAs="Leis Municipais"/>"#
+    )?;
+    writeln!(
+        saida,
+        r#"        <TLCOrganization eId="municipio" href="/ontology/organization/br/municipio" showAs="Município"/>"#
+    )?;
+    writeln!(saida, "      </references>")?;
+    writeln!(saida, "    </meta>")
+}
+
+// os ids seguem a hierarquia, como em art_1__para_2__point_3
+fn escreve_dispositivo(
+    saida: &mut dyn Write,
+    dispositivo: &Dispositivo,
+    pai: Option<&str>,
+    indice: usize,
+    nivel: usize,
+) -> io::Result<()> {
+    let (elemento, prefixo) = elemento(dispositivo.tipo);
+    let e_id = match pai {
+        Some(pai) => format!("{}__{}_{}", pai, prefixo, indice),
+        None => format!("{}_{}", prefixo, indice),
+    };
+    let recuo = "  ".repeat(nivel);
+
+    writeln!(saida, r#"{}<{} eId="{}">"#, recuo, elemento, e_id)?;
+    writeln!(
+        saida,
+        "{}  <num>{}</num>",
+        recuo,
+        escapa(&dispositivo.rotulo)
+    )?;
+    if dispositivo.filhos.is_empty() {
+        escreve_paragrafos(saida, "content", &dispositivo.texto, nivel + 1)?;
+    } else {
+        if !dispositivo.texto.trim().is_empty() {
+            escreve_paragrafos(saida, "intro", &dispositivo.texto, nivel + 1)?;
+        }
+        for (indice, filho) in (1..).zip(&dispositivo.filhos) {
+            escreve_dispositivo(saida, filho, Some(&e_id), indice, nivel + 1)?;
+        }
+    }
+    writeln!(saida, "{}</{}>", recuo, elemento)
+}
+
+fn escreve_paragrafos(
+    saida: &mut dyn Write,
+    elemento: &str,
+    texto: &str,
+    nivel: usize,
+) -> io::Result<()> {
+    let recuo = "  ".repeat(nivel);
+    let linhas = linhas(texto);
+
+    writeln!(saida, "{}<{}>", recuo, elemento)?;
+    if linhas.is_empty() {
+        writeln!(saida, "{}  <p/>", recuo)?;
+    }
+    for linha in linhas {
+        writeln!(saida, "{}  <p>{}</p>", recuo, escapa(linha))?;
+    }
+    writeln!(saida, "{}</{}>", recuo, elemento)
+}
+
+fn elemento(tipo: TipoDispositivo) -> (&'static str, &'static str) {
+    match tipo {
+        TipoDispositivo::Artigo => ("article", "art"),
+        TipoDispositivo::Paragrafo => ("paragraph", "para"),
+        TipoDispositivo::Inciso => ("point", "point"),
+        TipoDispositivo::Alinea => ("alinea", "alinea"),
+        TipoDispositivo::Item => ("indent", "indent"),
+    }
+}
+
+// tudo o que vem antes do primeiro artigo ou capítulo, como "O PREFEITO MUNICIPAL ... sanciono a seguinte Lei:"
+pub fn preambulo(texto: &str) -> Vec<&str> {
+    linhas(texto)
+        .into_iter()
+        .take_while(|linha| !INICIO_DO_CORPO_REGEX.is_match(linha))
+        .collect()
+}
+
+// o local, a data e as assinaturas ficam no fim do texto do último dispositivo
+fn separa_fecho(artigos: &[Dispositivo]) -> (Vec<Dispositivo>, Vec<String>) {
+    let mut artigos = artigos.to_vec();
+    let fecho = artigos
+        .last_mut()
+        .map(separa_fecho_do_dispositivo)
+        .unwrap_or_default();
+    (artigos, fecho)
+}
+
+fn separa_fecho_do_dispositivo(dispositivo: &mut Dispositivo) -> Vec<String> {
+    if let Some(ultimo) = dispositivo.filhos.last_mut() {
+        return separa_fecho_do_dispositivo(ultimo);
+    }
+
+    let linhas = dispositivo
+        .texto
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<String>>();
+    match linhas
+        .iter()
+        .skip(1)
+        .position(|linha| FECHO_REGEX.is_match(linha))
+    {
+        Some(posicao) => {
+            dispositivo.texto = linhas[..=posicao].join("\n");
+            linhas[posicao + 1..].to_vec()
+        }
+        None => Vec::new(),
+    }
+}
+
+fn marca_data(linha: &str) -> String {
+    let data = FECHO_REGEX.captures(linha).and_then(|captures| {
+        let data = captures.name("data")?;
+        data_iso(data.as_str()).map(|iso| (data, iso))
+    });
+    match data {
+        Some((data, iso)) => format!(
+            r#"{}<date date="{}">{}</date>{}"#,
+            escapa(&linha[..data.start()]),
+            iso,
+            escapa(data.as_str()),
+            escapa(&linha[data.end()..])
+        ),
+        None => escapa(linha),
+    }
+}
+
+fn linhas(texto: &str) -> Vec<&str> {
+    texto
+        .lines()
+        .map(str::trim)
+        .filter(|linha| !linha.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exportacao_akn::{escreve_akn, frbr_uri, nome_do_documento};
+    use crate::exportacao_lexml::LOCALIDADE_PADRAO;
+    use crate::parser::{parse_html_to_lei, Lei};
+    use crate::xml::valida_esquema;
+    use roxmltree::Document;
+
+    const ESQUEMA: &str = "resources/akn/akomantoso30.xsd";
+
+    fn escreve(lei: &Lei) -> String {
+        let mut saida = Vec::new();
+        escreve_akn(&mut saida, lei, &frbr_uri(lei).unwrap(), LOCALIDADE_PADRAO).unwrap();
+        String::from_utf8(saida).unwrap()
+    }
+
+    #[test]
+    fn should_export_preamble_articles_and_signatures_as_akoma_ntoso() {
+        let lei = parse_html_to_lei(
+            "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+            vec!["complementar".to_string()],
+            None,
+        )
+        .unwrap();
+
+        let xml = escreve(&lei);
+
+        Document::parse(&xml).unwrap();
+        assert_eq!(
+            nome_do_documento(&frbr_uri(&lei).unwrap()),
+            "br_act_lei.complementar_2019-02-22_122.xml"
+        );
+        assert!(xml.contains(r#"<act name="lei.complementar">"#));
+        assert!(xml.contains(
+            r#"<FRBRalias value="urn:lex:br;bahia;feira.de.santana:municipal:lei.complementar:2019-02-22;122" name="urn:lex"/>"#
+        ));
+        assert!(xml.contains("<preamble>\n      <p>O PREFEITO MUNICIPAL DE FEIRA DE SANTANA"));
+        assert!(xml.contains(r#"<article eId="art_10">"#));
+        assert!(xml.contains(
+            r#"<p>Gabinete do Prefeito, <date date="2019-02-22">22 de fevereiro de 2019</date></p>"#
+        ));
+        assert!(xml.contains("<p><signature>COLBERT MARTINS DA SILVA FILHO</signature></p>"));
+        assert!(!xml.contains("<p>COLBERT MARTINS DA SILVA FILHO</p>"));
+    }
+
+    #[test]
+    fn should_export_paragraphs_and_attachment_as_akoma_ntoso() {
+        let lei = parse_html_to_lei(
+            "resources/integration_tests/leis/complementar/LeisMunicipais-com-br-Decreto-5907-1995.html",
+            vec!["complementar".to_string()],
+            None,
+        )
+        .unwrap();
+        let lei = Lei {
+            documento: Some("https://leismunicipais.com.br/a?b=1&c=2".to_string()),
+            ..lei
+        };
+
+        let xml = escreve(&lei);
+
+        Document::parse(&xml).unwrap();
+        assert!(xml.contains(r#"<point eId="art_2__point_1">"#));
+        assert!(xml.contains(r#"<paragraph eId="art_5__para_3">"#));
+        assert!(xml.contains("<p><signature>JOSÉ RAIMUNDO PEREIRA DE AZEVEDO</signature></p>"));
+        assert!(xml.contains(
+            r#"<documentRef href="https://leismunicipais.com.br/a?b=1&amp;c=2" showAs="Documento"/>"#
+        ));
+    }
+
+    #[test]
+    #[ignore = "precisa do xmllint e de resources/akn/akomantoso30.xsd"]
+    fn should_validate_akoma_ntoso_against_the_official_schema() {
+        for arquivo in &[
+            "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+            "resources/integration_tests/leis/complementar/LeisMunicipais-com-br-Decreto-5907-1995.html",
+        ] {
+            let lei = Lei {
+                documento: Some("https://leismunicipais.com.br/a?b=1&c=2".to_string()),
+                ..parse_html_to_lei(arquivo, vec!["complementar".to_string()], None).unwrap()
+            };
+
+            valida_esquema(&escreve(&lei), ESQUEMA);
+        }
+    }
+}
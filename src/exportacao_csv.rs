@@ -0,0 +1,243 @@
+use crate::parser::Lei;
+use crate::parser_executor::Folder;
+use csv::{Terminator, WriterBuilder};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Uma coluna do CSV, com o nome do campo correspondente da [`Lei`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Coluna {
+    Titulo,
+    Tipo,
+    Numero,
+    Ano,
+    Data,
+    Categoria,
+    Resumo,
+    Texto,
+    TextoConsolidado,
+    Documento,
+    Perfil,
+    Codificacao,
+}
+
+impl Coluna {
+    /// Colunas gravadas quando nenhuma é escolhida.
+    pub const PADRAO: [Coluna; 4] = [
+        Coluna::Titulo,
+        Coluna::Categoria,
+        Coluna::Resumo,
+        Coluna::Documento,
+    ];
+
+    fn nome(self) -> &'static str {
+        match self {
+            Coluna::Titulo => "titulo",
+            Coluna::Tipo => "tipo",
+            Coluna::Numero => "numero",
+            Coluna::Ano => "ano",
+            Coluna::Data => "data",
+            Coluna::Categoria => "categoria",
+            Coluna::Resumo => "resumo",
+            Coluna::Texto => "texto",
+            Coluna::TextoConsolidado => "texto_consolidado",
+            Coluna::Documento => "documento",
+            Coluna::Perfil => "perfil",
+            Coluna::Codificacao => "codificacao",
+        }
+    }
+
+    fn valor(self, lei: &Lei, limite_do_texto: Option<usize>) -> String {
+        let identificacao = &lei.identificacao;
+        match self {
+            Coluna::Titulo => lei.titulo.clone(),
+            Coluna::Tipo => identificacao
+                .tipo
+                .as_ref()
+                .map_or_else(String::new, ToString::to_string),
+            Coluna::Numero => identificacao
+                .numero
+                .as_ref()
+                .map_or_else(String::new, ToString::to_string),
+            Coluna::Ano => identificacao
+                .ano
+                .as_ref()
+                .map_or_else(String::new, ToString::to_string),
+            Coluna::Data => identificacao.data.clone().unwrap_or_default(),
+            Coluna::Categoria => lei.categoria.clone(),
+            Coluna::Resumo => lei.resumo.clone(),
+            Coluna::Texto => trunca(&lei.texto, limite_do_texto),
+            Coluna::TextoConsolidado => trunca(&lei.texto_consolidado, limite_do_texto),
+            Coluna::Documento => lei.documento.clone().unwrap_or_default(),
+            Coluna::Perfil => lei.perfil.clone(),
+            Coluna::Codificacao => lei.codificacao.clone(),
+        }
+    }
+}
+
+impl FromStr for Coluna {
+    type Err = String;
+
+    fn from_str(valor: &str) -> Result<Self, Self::Err> {
+        let nome = valor.trim().to_lowercase();
+        COLUNAS
+            .iter()
+            .find(|coluna| coluna.nome() == nome)
+            .copied()
+            .ok_or_else(|| format!("Coluna desconhecida: {}", valor))
+    }
+}
+
+const COLUNAS: [Coluna; 12] = [
+    Coluna::Titulo,
+    Coluna::Tipo,
+    Coluna::Numero,
+    Coluna::Ano,
+    Coluna::Data,
+    Coluna::Categoria,
+    Coluna::Resumo,
+    Coluna::Texto,
+    Coluna::TextoConsolidado,
+    Coluna::Documento,
+    Coluna::Perfil,
+    Coluna::Codificacao,
+];
+
+pub fn escreve_leis_csv(
+    saida: &mut dyn Write,
+    leis: &[Lei],
+    colunas: &[Coluna],
+    limite_do_texto: Option<usize>,
+    bom: bool,
+) -> io::Result<()> {
+    if bom {
+        saida.write_all(BOM)?;
+    }
+    let mut writer = WriterBuilder::new()
+        .terminator(Terminator::CRLF)
+        .from_writer(saida);
+    writer.write_record(colunas.iter().map(|coluna| coluna.nome()))?;
+    for lei in leis {
+        writer.write_record(
+            colunas
+                .iter()
+                .map(|coluna| coluna.valor(lei, limite_do_texto)),
+        )?;
+    }
+    writer.flush()
+}
+
+pub fn escreve_pastas_csv(
+    saida: &mut dyn Write,
+    directories: &HashMap<String, Folder>,
+    bom: bool,
+) -> io::Result<()> {
+    if bom {
+        saida.write_all(BOM)?;
+    }
+    let mut nomes = directories.keys().collect::<Vec<&String>>();
+    nomes.sort();
+
+    let mut writer = WriterBuilder::new()
+        .terminator(Terminator::CRLF)
+        .from_writer(saida);
+    writer.write_record(&["diretorio", "total", "parseados", "com_erros"])?;
+    for nome in nomes {
+        let folder = &directories[nome];
+        writer.write_record(&[
+            nome.to_string(),
+            folder.total.to_string(),
+            folder.parsed.to_string(),
+            folder.failed.len().to_string(),
+        ])?;
+    }
+    writer.flush()
+}
+
+fn trunca(texto: &str, limite: Option<usize>) -> String {
+    match limite {
+        Some(limite) if texto.chars().count() > limite => {
+            let mut truncado = texto.chars().take(limite).collect::<String>();
+            truncado.push('…');
+            truncado
+        }
+        _ => texto.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exportacao_csv::{escreve_leis_csv, escreve_pastas_csv, Coluna};
+    use crate::parser::parse_html_to_lei;
+    use crate::parser_executor::Folder;
+    use std::collections::HashMap;
+
+    #[test]
+    fn should_write_selected_columns_with_rfc_4180_quoting() {
+        let lei = parse_html_to_lei(
+            "resources/unit_tests/LeisMunicipais-com-br-Decreto-1-1984.html",
+            vec!["decretos".to_string()],
+            None,
+        )
+        .unwrap();
+        let mut saida = Vec::new();
+
+        escreve_leis_csv(
+            &mut saida,
+            &[lei],
+            &[Coluna::Titulo, Coluna::Numero, Coluna::Texto],
+            Some(10),
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(saida).unwrap(),
+            "\u{feff}titulo,numero,texto\r\n\"DECRETO Nº 1/84, de 05 de janeiro de 1984\",1,O PRESIDEN…\r\n"
+        );
+    }
+
+    #[test]
+    fn should_write_folder_stats() {
+        let mut directories = HashMap::new();
+        directories.insert(
+            "orgânica".to_string(),
+            Folder {
+                total: 2,
+                parsed: 1,
+                cached: 0,
+                failed: vec!["orgânica/lei.html".to_string()],
+            },
+        );
+        directories.insert(
+            "complementar".to_string(),
+            Folder {
+                total: 3,
+                parsed: 3,
+                cached: 0,
+                failed: vec![],
+            },
+        );
+        let mut saida = Vec::new();
+
+        escreve_pastas_csv(&mut saida, &directories, false).unwrap();
+
+        assert_eq!(
+            String::from_utf8(saida).unwrap(),
+            "diretorio,total,parseados,com_erros\r\ncomplementar,3,3,0\r\norgânica,2,1,1\r\n"
+        );
+    }
+
+    #[test]
+    fn should_parse_coluna_names() {
+        assert_eq!("Texto_Consolidado".parse(), Ok(Coluna::TextoConsolidado));
+        assert_eq!(
+            "ementa".parse::<Coluna>(),
+            Err("Coluna desconhecida: ementa".to_string())
+        );
+    }
+}
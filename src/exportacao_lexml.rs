@@ -0,0 +1,213 @@
+use crate::identificacao::TipoNorma;
+use crate::parser::Lei;
+use crate::xml::{escapa, DECLARACAO};
+use std::io::{self, Write};
+
+pub const LOCALIDADE_PADRAO: &str = "br;bahia;feira.de.santana";
+
+const NAMESPACE: &str = "http://www.lexml.gov.br/oai_lexml";
+const ESQUEMA: &str = "http://projeto.lexml.gov.br/esquemas/oai_lexml.xsd";
+// resolvedor de URNs do LexML, usado no Item das leis sem o documento original
+const RESOLVEDOR: &str = "https://www.lexml.gov.br/urn/";
+
+// urn:lex:<local>:<autoridade>:<tipo>:<data ou ano>;<número>
+pub fn urn(lei: &Lei, localidade: &str) -> Option<String> {
+    let identificacao = &lei.identificacao;
+    let tipo = nome_do_tipo(identificacao.tipo?);
+    let numero = identificacao.numero?;
+    let data = identificacao
+        .data
+        .clone()
+        .or_else(|| identificacao.ano.map(|ano| ano.to_string()))?;
+    Some(format!(
+        "urn:lex:{}:municipal:{}:{};{}",
+        localidade, tipo, data, numero
+    ))
+}
+
+pub fn nome_do_arquivo(urn: &str) -> String {
+    format!(
+        "{}.xml",
+        urn.replace(|caractere: char| caractere == ':' || caractere == ';', "_")
+    )
+}
+
+pub fn escreve_lexml(
+    saida: &mut dyn Write,
+    lei: &Lei,
+    urn: &str,
+    id_publicador: u32,
+) -> io::Result<()> {
+    writeln!(saida, "{}", DECLARACAO)?;
+    writeln!(
+        saida,
+        r#"<LexML xmlns="{0}" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="{0} {1}">"#,
+        NAMESPACE, ESQUEMA
+    )?;
+    let item = lei
+        .documento
+        .clone()
+        .unwrap_or_else(|| format!("{}{}", RESOLVEDOR, urn));
+    writeln!(
+        saida,
+        r#"  <Item formato="{}" idPublicador="{}" tipo="conteudo">{}</Item>"#,
+        formato(&item),
+        id_publicador,
+        escapa(&item)
+    )?;
+    writeln!(
+        saida,
+        "  <DocumentoIndividual>{}</DocumentoIndividual>",
+        escapa(urn)
+    )?;
+    writeln!(saida, "  <Epigrafe>{}</Epigrafe>", escapa(&lei.titulo))?;
+    writeln!(saida, "  <Ementa>{}</Ementa>", escapa(&lei.resumo))?;
+    if !lei.categorias.is_empty() {
+        writeln!(
+            saida,
+            "  <Indexacao>{}</Indexacao>",
+            escapa(&lei.categorias.join(", "))
+        )?;
+    }
+    writeln!(saida, "</LexML>")
+}
+
+// tipo MIME pela extensão do documento original; o resolvedor do LexML devolve uma página HTML
+fn formato(item: &str) -> &'static str {
+    let extensao = item.rsplit('.').next().unwrap_or_default();
+    match extensao.to_lowercase().as_str() {
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "rtf" => "application/rtf",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        _ => "text/html",
+    }
+}
+
+pub fn nome_do_tipo(tipo: TipoNorma) -> &'static str {
+    match tipo {
+        TipoNorma::Lei => "lei",
+        TipoNorma::LeiComplementar => "lei.complementar",
+        TipoNorma::LeiOrganica => "lei.organica",
+        TipoNorma::EmendaLeiOrganica => "emenda.lei.organica",
+        TipoNorma::Decreto => "decreto",
+        TipoNorma::DecretoLegislativo => "decreto.legislativo",
+        TipoNorma::Resolucao => "resolucao",
+        TipoNorma::Portaria => "portaria",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exportacao_lexml::{escreve_lexml, nome_do_arquivo, urn, LOCALIDADE_PADRAO};
+    use crate::identificacao::Identificacao;
+    use crate::parser::{parse_html_to_lei, Lei};
+    use crate::xml::valida_esquema;
+
+    const ESQUEMA: &str = "resources/lexml/oai_lexml.xsd";
+
+    fn lei_complementar() -> Lei {
+        parse_html_to_lei(
+            "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+            vec!["complementar".to_string(), "previdência".to_string()],
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_build_urn_from_tipo_data_and_numero() {
+        let urn = urn(&lei_complementar(), LOCALIDADE_PADRAO).unwrap();
+
+        assert_eq!(
+            urn,
+            "urn:lex:br;bahia;feira.de.santana:municipal:lei.complementar:2019-02-22;122"
+        );
+        assert_eq!(
+            nome_do_arquivo(&urn),
+            "urn_lex_br_bahia_feira.de.santana_municipal_lei.complementar_2019-02-22_122.xml"
+        );
+    }
+
+    #[test]
+    fn should_use_the_year_when_there_is_no_date_and_skip_unidentified_leis() {
+        let lei = Lei {
+            identificacao: Identificacao {
+                data: None,
+                ..lei_complementar().identificacao
+            },
+            ..lei_complementar()
+        };
+        assert_eq!(
+            urn(&lei, "br;bahia"),
+            Some("urn:lex:br;bahia:municipal:lei.complementar:2019;122".to_string())
+        );
+
+        let lei = Lei {
+            identificacao: Identificacao::default(),
+            ..lei_complementar()
+        };
+        assert_eq!(urn(&lei, LOCALIDADE_PADRAO), None);
+    }
+
+    #[test]
+    fn should_write_lexml_metadata() {
+        let lei = Lei {
+            titulo: "LEI COMPLEMENTAR Nº 122 & outras".to_string(),
+            resumo: "Dispõe sobre <pensões>.".to_string(),
+            documento: None,
+            ..lei_complementar()
+        };
+        let mut saida = Vec::new();
+
+        escreve_lexml(&mut saida, &lei, "urn:lex:br:municipal:lei:2019;1", 7).unwrap();
+
+        assert_eq!(
+            String::from_utf8(saida).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<LexML xmlns="http://www.lexml.gov.br/oai_lexml" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://www.lexml.gov.br/oai_lexml http://projeto.lexml.gov.br/esquemas/oai_lexml.xsd">
+  <Item formato="text/html" idPublicador="7" tipo="conteudo">https://www.lexml.gov.br/urn/urn:lex:br:municipal:lei:2019;1</Item>
+  <DocumentoIndividual>urn:lex:br:municipal:lei:2019;1</DocumentoIndividual>
+  <Epigrafe>LEI COMPLEMENTAR Nº 122 &amp; outras</Epigrafe>
+  <Ementa>Dispõe sobre &lt;pensões&gt;.</Ementa>
+  <Indexacao>complementar, previdência</Indexacao>
+</LexML>
+"#
+        );
+    }
+
+    #[test]
+    fn should_write_the_original_document_as_item() {
+        let mut saida = Vec::new();
+
+        escreve_lexml(
+            &mut saida,
+            &lei_complementar(),
+            "urn:lex:br:municipal:lei:2019;1",
+            7,
+        )
+        .unwrap();
+
+        assert!(String::from_utf8(saida).unwrap().contains(
+            r#"<Item formato="application/msword" idPublicador="7" tipo="conteudo">https://leis.s3.amazonaws.com/originais/feira-de-santana-ba/2019/lc-122-2019-feira_de_santana-ba.doc</Item>"#
+        ));
+    }
+
+    #[test]
+    #[ignore = "precisa do xmllint e de resources/lexml/oai_lexml.xsd"]
+    fn should_validate_lexml_against_the_official_schema() {
+        for documento in &[None, lei_complementar().documento] {
+            let lei = Lei {
+                documento: documento.clone(),
+                ..lei_complementar()
+            };
+            let urn = urn(&lei, LOCALIDADE_PADRAO).unwrap();
+            let mut saida = Vec::new();
+
+            escreve_lexml(&mut saida, &lei, &urn, 7).unwrap();
+
+            valida_esquema(&String::from_utf8(saida).unwrap(), ESQUEMA);
+        }
+    }
+}
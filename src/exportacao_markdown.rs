@@ -0,0 +1,262 @@
+use crate::cache::impressao_digital;
+use crate::estrutura::Dispositivo;
+use crate::exportacao_akn::preambulo;
+use crate::exportacao_lexml::nome_do_tipo;
+use crate::identificacao::remove_acentos;
+use crate::parser::Lei;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+const LIMITE_DO_NOME: usize = 80;
+
+// tipo-numero-ano, como em lei-complementar-122-2019
+pub fn nome_da_norma(lei: &Lei) -> Option<String> {
+    let identificacao = &lei.identificacao;
+    let tipo = nome_do_tipo(identificacao.tipo?).replace('.', "-");
+    let numero = identificacao.numero?;
+    Some(match identificacao.ano {
+        Some(ano) => format!("{}-{}-{}", tipo, numero, ano),
+        None => format!("{}-{}", tipo, numero),
+    })
+}
+
+// o nome depende só da própria lei, e não das outras leis exportadas, para que uma nova
+// exportação altere apenas os arquivos das leis que mudaram; o sufixo vem do título, que traz a
+// data, e separa as leis com o mesmo tipo, número e ano ou com o mesmo slug
+pub fn caminho_do_arquivo(lei: &Lei) -> PathBuf {
+    let nome = nome_da_norma(lei).unwrap_or_else(|| slug(&lei.titulo));
+    let sufixo = impressao_digital(lei.titulo.bytes()) >> 32;
+    pasta(&lei.categoria).join(format!("{}-{:08x}.md", nome, sufixo))
+}
+
+pub fn escreve_markdown(saida: &mut dyn Write, lei: &Lei) -> io::Result<()> {
+    writeln!(saida, "---")?;
+    writeln!(saida, "titulo: {}", yaml(&lei.titulo))?;
+    writeln!(saida, "resumo: {}", yaml(&lei.resumo))?;
+    writeln!(
+        saida,
+        "documento: {}",
+        lei.documento
+            .as_deref()
+            .map_or_else(|| "null".to_string(), yaml)
+    )?;
+    writeln!(
+        saida,
+        "data: {}",
+        lei.identificacao.data.as_deref().unwrap_or("null")
+    )?;
+    writeln!(saida, "---")?;
+    writeln!(saida)?;
+    writeln!(saida, "# {}", escapa_linha(&lei.titulo))?;
+
+    let preambulo = if lei.artigos.is_empty() {
+        lei.texto
+            .lines()
+            .map(str::trim)
+            .filter(|linha| !linha.is_empty())
+            .collect()
+    } else {
+        preambulo(&lei.texto)
+    };
+    for linha in preambulo {
+        writeln!(saida)?;
+        writeln!(saida, "{}", escapa_linha(linha))?;
+    }
+
+    for artigo in &lei.artigos {
+        writeln!(saida)?;
+        writeln!(saida, "## {}", escapa_linha(&artigo.rotulo))?;
+        escreve_linhas(saida, &artigo.texto)?;
+        for filho in &artigo.filhos {
+            escreve_dispositivo(saida, filho)?;
+        }
+    }
+    Ok(())
+}
+
+fn escreve_dispositivo(saida: &mut dyn Write, dispositivo: &Dispositivo) -> io::Result<()> {
+    let mut linhas = dispositivo
+        .texto
+        .lines()
+        .map(str::trim)
+        .filter(|linha| !linha.is_empty());
+    writeln!(saida)?;
+    writeln!(
+        saida,
+        "**{}** {}",
+        dispositivo.rotulo,
+        linhas.next().map(escapa_linha).unwrap_or_default()
+    )?;
+    escreve_linhas(saida, &linhas.collect::<Vec<&str>>().join("\n"))?;
+    for filho in &dispositivo.filhos {
+        escreve_dispositivo(saida, filho)?;
+    }
+    Ok(())
+}
+
+fn escreve_linhas(saida: &mut dyn Write, texto: &str) -> io::Result<()> {
+    for linha in texto
+        .lines()
+        .map(str::trim)
+        .filter(|linha| !linha.is_empty())
+    {
+        writeln!(saida)?;
+        writeln!(saida, "{}", escapa_linha(linha))?;
+    }
+    Ok(())
+}
+
+// evita que o início da linha seja lido como título, citação ou lista
+fn escapa_linha(linha: &str) -> String {
+    let fim_do_numero = linha
+        .find(|caractere: char| !caractere.is_ascii_digit())
+        .filter(|posicao| *posicao > 0 && linha[*posicao..].starts_with(". "));
+    if let Some(posicao) = fim_do_numero {
+        return format!("{}\\{}", &linha[..posicao], &linha[posicao..]);
+    }
+    if linha.starts_with(|caractere: char| "#>-+*".contains(caractere)) {
+        return format!("\\{}", linha);
+    }
+    linha.to_string()
+}
+
+fn yaml(texto: &str) -> String {
+    let mut escapado = String::with_capacity(texto.len() + 2);
+    escapado.push('"');
+    for caractere in texto.chars() {
+        match caractere {
+            '"' => escapado.push_str("\\\""),
+            '\\' => escapado.push_str("\\\\"),
+            '\n' => escapado.push_str("\\n"),
+            '\r' => {}
+            _ => escapado.push(caractere),
+        }
+    }
+    escapado.push('"');
+    escapado
+}
+
+// cada nível da categoria vira uma subpasta, e as leis sem categoria ficam na raiz
+fn pasta(categoria: &str) -> PathBuf {
+    categoria
+        .split('/')
+        .filter(|nivel| !nivel.trim().is_empty())
+        .map(slug)
+        .collect()
+}
+
+fn slug(texto: &str) -> String {
+    let mut slug = String::new();
+    for caractere in remove_acentos(texto).to_lowercase().chars() {
+        if caractere.is_ascii_alphanumeric() {
+            slug.push(caractere);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.chars().take(LIMITE_DO_NOME).collect::<String>();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "norma".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exportacao_markdown::{caminho_do_arquivo, escapa_linha, escreve_markdown};
+    use crate::identificacao::Identificacao;
+    use crate::parser::{parse_html_to_lei, Lei};
+    use std::path::PathBuf;
+
+    fn lei_complementar() -> Lei {
+        parse_html_to_lei(
+            "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+            vec!["orgânica".to_string()],
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_name_files_by_categoria_tipo_numero_ano_and_titulo() {
+        let sem_identificacao = Lei {
+            titulo: "Regimento Interno da Câmara".to_string(),
+            identificacao: Identificacao::default(),
+            ..lei_complementar()
+        };
+
+        assert_eq!(
+            caminho_do_arquivo(&lei_complementar()),
+            PathBuf::from("organica/lei-complementar-122-2019-a76234f9.md")
+        );
+        assert_eq!(
+            caminho_do_arquivo(&sem_identificacao),
+            PathBuf::from("organica/regimento-interno-da-camara-dfa5b054.md")
+        );
+    }
+
+    #[test]
+    fn should_nest_categoria_levels_and_keep_leis_without_categoria_at_the_root() {
+        let aninhada = Lei {
+            categoria: "Decretos/1995".to_string(),
+            categorias: vec!["Decretos".to_string(), "1995".to_string()],
+            ..lei_complementar()
+        };
+        let sem_categoria = Lei {
+            categoria: String::new(),
+            categorias: vec![],
+            ..lei_complementar()
+        };
+
+        assert_eq!(
+            caminho_do_arquivo(&aninhada),
+            PathBuf::from("decretos/1995/lei-complementar-122-2019-a76234f9.md")
+        );
+        assert_eq!(
+            caminho_do_arquivo(&sem_categoria),
+            PathBuf::from("lei-complementar-122-2019-a76234f9.md")
+        );
+    }
+
+    #[test]
+    fn should_separate_leis_with_the_same_number_by_titulo() {
+        let republicada = Lei {
+            titulo: "LEI COMPLEMENTAR Nº 122, DE 22 DE FEVEREIRO DE 2019 (republicação)"
+                .to_string(),
+            ..lei_complementar()
+        };
+
+        let caminho = caminho_do_arquivo(&republicada);
+        assert_ne!(caminho, caminho_do_arquivo(&lei_complementar()));
+        assert!(caminho
+            .to_string_lossy()
+            .starts_with("organica/lei-complementar-122-2019-"));
+    }
+
+    #[test]
+    fn should_escape_lines_that_look_like_markdown() {
+        assert_eq!(escapa_linha("# 1"), "\\# 1");
+        assert_eq!(escapa_linha("1. Fica"), "1\\. Fica");
+        assert_eq!(escapa_linha("1.802/95"), "1.802/95");
+    }
+
+    #[test]
+    fn should_write_front_matter_and_one_heading_per_article() {
+        let lei = Lei {
+            resumo: "Altera a \"Lei\" da previdência".to_string(),
+            ..lei_complementar()
+        };
+        let mut saida = Vec::new();
+
+        escreve_markdown(&mut saida, &lei).unwrap();
+
+        let markdown = String::from_utf8(saida).unwrap();
+        assert!(markdown.starts_with(
+            "---\ntitulo: \"LEI COMPLEMENTAR Nº 122, DE 22 DE FEVEREIRO DE 2019\"\nresumo: \"Altera a \\\"Lei\\\" da previdência\"\ndocumento: \"https://leis.s3.amazonaws.com/originais/feira-de-santana-ba/2019/lc-122-2019-feira_de_santana-ba.doc\"\ndata: 2019-02-22\n---\n\n# LEI COMPLEMENTAR Nº 122, DE 22 DE FEVEREIRO DE 2019\n\nO PREFEITO MUNICIPAL DE FEIRA DE SANTANA"
+        ));
+        assert!(markdown.contains("\n\n## Art. 1º\n\nFica alterado o artigo 48"));
+        assert_eq!(markdown.matches("\n## ").count(), 10);
+    }
+}
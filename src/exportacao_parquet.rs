@@ -0,0 +1,366 @@
+use crate::estrutura::Dispositivo;
+use crate::exportacao::nome_serializado;
+use crate::parser::Lei;
+use arrow::array::{ArrayRef, Int32Array, Int64Array, StringArray, UInt32Array, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::schema::types::ColumnPath;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+// colunas com poucos valores distintos, gravadas com dicionário
+const COLUNAS_COM_DICIONARIO: [&str; 4] = ["categoria", "tipo", "perfil", "codificacao"];
+
+pub fn escreve_leis_parquet(
+    leis: &[Lei],
+    caminho: &Path,
+    caminho_dos_artigos: &Path,
+    caminho_das_referencias: &Path,
+) -> Result<(), ParquetError> {
+    escreve(caminho, tabela_de_leis(leis)?)?;
+    escreve(caminho_dos_artigos, tabela_de_artigos(leis)?)?;
+    escreve(caminho_das_referencias, tabela_de_referencias(leis)?)
+}
+
+fn tabela_de_leis(leis: &[Lei]) -> Result<RecordBatch, ParquetError> {
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("titulo", DataType::Utf8, false),
+        Field::new("tipo", DataType::Utf8, true),
+        Field::new("numero", DataType::UInt32, true),
+        Field::new("ano", DataType::Int32, true),
+        Field::new("data", DataType::Utf8, true),
+        Field::new("categoria", DataType::Utf8, false),
+        Field::new("resumo", DataType::Utf8, false),
+        Field::new("texto", DataType::Utf8, false),
+        Field::new("texto_consolidado", DataType::Utf8, false),
+        Field::new("documento", DataType::Utf8, true),
+        Field::new("perfil", DataType::Utf8, false),
+        Field::new("codificacao", DataType::Utf8, false),
+    ]);
+    let tipos = leis
+        .iter()
+        .map(|lei| lei.identificacao.tipo.as_ref().and_then(nome_serializado))
+        .collect::<Vec<Option<String>>>();
+    let colunas: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(
+            (1..).take(leis.len()).collect::<Vec<i64>>(),
+        )),
+        textos(leis, |lei| Some(lei.titulo.as_str())),
+        Arc::new(StringArray::from(
+            tipos
+                .iter()
+                .map(Option::as_deref)
+                .collect::<Vec<Option<&str>>>(),
+        )),
+        Arc::new(UInt32Array::from(
+            leis.iter()
+                .map(|lei| lei.identificacao.numero)
+                .collect::<Vec<Option<u32>>>(),
+        )),
+        Arc::new(Int32Array::from(
+            leis.iter()
+                .map(|lei| lei.identificacao.ano)
+                .collect::<Vec<Option<i32>>>(),
+        )),
+        textos(leis, |lei| lei.identificacao.data.as_deref()),
+        textos(leis, |lei| Some(lei.categoria.as_str())),
+        textos(leis, |lei| Some(lei.resumo.as_str())),
+        textos(leis, |lei| Some(lei.texto.as_str())),
+        textos(leis, |lei| Some(lei.texto_consolidado.as_str())),
+        textos(leis, |lei| lei.documento.as_deref()),
+        textos(leis, |lei| Some(lei.perfil.as_str())),
+        textos(leis, |lei| Some(lei.codificacao.as_str())),
+    ];
+    Ok(RecordBatch::try_new(Arc::new(schema), colunas)?)
+}
+
+fn tabela_de_artigos(leis: &[Lei]) -> Result<RecordBatch, ParquetError> {
+    let mut linhas = Vec::new();
+    for (lei_id, lei) in (1..).zip(leis) {
+        achata_dispositivos(&lei.artigos, lei_id, None, &mut linhas);
+    }
+
+    let schema = Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("lei_id", DataType::Int64, false),
+        Field::new("pai_id", DataType::Int64, true),
+        Field::new("ordem", DataType::Int64, false),
+        Field::new("tipo", DataType::Utf8, false),
+        Field::new("rotulo", DataType::Utf8, false),
+        Field::new("texto", DataType::Utf8, false),
+    ]);
+    let colunas: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(
+            (1..).take(linhas.len()).collect::<Vec<i64>>(),
+        )),
+        Arc::new(Int64Array::from(
+            linhas
+                .iter()
+                .map(|linha| linha.lei_id)
+                .collect::<Vec<i64>>(),
+        )),
+        Arc::new(Int64Array::from(
+            linhas
+                .iter()
+                .map(|linha| linha.pai_id)
+                .collect::<Vec<Option<i64>>>(),
+        )),
+        Arc::new(Int64Array::from(
+            linhas.iter().map(|linha| linha.ordem).collect::<Vec<i64>>(),
+        )),
+        Arc::new(StringArray::from(
+            linhas
+                .iter()
+                .map(|linha| linha.tipo.as_str())
+                .collect::<Vec<&str>>(),
+        )),
+        Arc::new(StringArray::from(
+            linhas
+                .iter()
+                .map(|linha| linha.dispositivo.rotulo.as_str())
+                .collect::<Vec<&str>>(),
+        )),
+        Arc::new(StringArray::from(
+            linhas
+                .iter()
+                .map(|linha| linha.dispositivo.texto.as_str())
+                .collect::<Vec<&str>>(),
+        )),
+    ];
+    Ok(RecordBatch::try_new(Arc::new(schema), colunas)?)
+}
+
+fn tabela_de_referencias(leis: &[Lei]) -> Result<RecordBatch, ParquetError> {
+    let linhas = (1..)
+        .zip(leis)
+        .flat_map(|(lei_id, lei)| {
+            lei.referencias
+                .iter()
+                .map(move |referencia| (lei_id, referencia))
+        })
+        .collect::<Vec<_>>();
+    let tipos = linhas
+        .iter()
+        .map(|(_, referencia)| referencia.tipo.as_ref().and_then(nome_serializado))
+        .collect::<Vec<Option<String>>>();
+
+    let schema = Schema::new(vec![
+        Field::new("lei_id", DataType::Int64, false),
+        Field::new("norma_id", DataType::UInt64, true),
+        Field::new("url", DataType::Utf8, true),
+        Field::new("tipo", DataType::Utf8, true),
+        Field::new("numero", DataType::UInt32, true),
+        Field::new("ano", DataType::Int32, true),
+        Field::new("data", DataType::Utf8, true),
+        Field::new("ementa", DataType::Utf8, true),
+        Field::new("posicao", DataType::UInt64, false),
+    ]);
+    let colunas: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(
+            linhas
+                .iter()
+                .map(|(lei_id, _)| *lei_id)
+                .collect::<Vec<i64>>(),
+        )),
+        Arc::new(UInt64Array::from(
+            linhas
+                .iter()
+                .map(|(_, referencia)| referencia.id)
+                .collect::<Vec<Option<u64>>>(),
+        )),
+        Arc::new(StringArray::from(
+            linhas
+                .iter()
+                .map(|(_, referencia)| referencia.url.as_deref())
+                .collect::<Vec<Option<&str>>>(),
+        )),
+        Arc::new(StringArray::from(
+            tipos
+                .iter()
+                .map(Option::as_deref)
+                .collect::<Vec<Option<&str>>>(),
+        )),
+        Arc::new(UInt32Array::from(
+            linhas
+                .iter()
+                .map(|(_, referencia)| referencia.numero)
+                .collect::<Vec<Option<u32>>>(),
+        )),
+        Arc::new(Int32Array::from(
+            linhas
+                .iter()
+                .map(|(_, referencia)| referencia.ano)
+                .collect::<Vec<Option<i32>>>(),
+        )),
+        Arc::new(StringArray::from(
+            linhas
+                .iter()
+                .map(|(_, referencia)| referencia.data.as_deref())
+                .collect::<Vec<Option<&str>>>(),
+        )),
+        Arc::new(StringArray::from(
+            linhas
+                .iter()
+                .map(|(_, referencia)| referencia.ementa.as_deref())
+                .collect::<Vec<Option<&str>>>(),
+        )),
+        Arc::new(UInt64Array::from(
+            linhas
+                .iter()
+                .map(|(_, referencia)| u64::try_from(referencia.posicao).unwrap_or(u64::MAX))
+                .collect::<Vec<u64>>(),
+        )),
+    ];
+    Ok(RecordBatch::try_new(Arc::new(schema), colunas)?)
+}
+
+struct LinhaDeArtigo<'a> {
+    lei_id: i64,
+    pai_id: Option<i64>,
+    ordem: i64,
+    tipo: String,
+    dispositivo: &'a Dispositivo,
+}
+
+// os ids seguem a ordem de inserção, então o id de cada linha é a sua posição + 1
+fn achata_dispositivos<'a>(
+    dispositivos: &'a [Dispositivo],
+    lei_id: i64,
+    pai_id: Option<i64>,
+    linhas: &mut Vec<LinhaDeArtigo<'a>>,
+) {
+    for (ordem, dispositivo) in (0..).zip(dispositivos) {
+        linhas.push(LinhaDeArtigo {
+            lei_id,
+            pai_id,
+            ordem,
+            tipo: nome_serializado(&dispositivo.tipo).unwrap_or_default(),
+            dispositivo,
+        });
+        let id = i64::try_from(linhas.len()).unwrap_or(i64::MAX);
+        achata_dispositivos(&dispositivo.filhos, lei_id, Some(id), linhas);
+    }
+}
+
+fn textos<'a>(leis: &'a [Lei], campo: impl Fn(&'a Lei) -> Option<&'a str>) -> ArrayRef {
+    Arc::new(StringArray::from(
+        leis.iter().map(campo).collect::<Vec<Option<&str>>>(),
+    ))
+}
+
+fn escreve(caminho: &Path, tabela: RecordBatch) -> Result<(), ParquetError> {
+    let mut propriedades = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_dictionary_enabled(false);
+    for coluna in &COLUNAS_COM_DICIONARIO {
+        propriedades = propriedades.set_column_dictionary_enabled(ColumnPath::from(*coluna), true);
+    }
+
+    let arquivo = File::create(caminho)?;
+    let mut writer = ArrowWriter::try_new(arquivo, tabela.schema(), Some(propriedades.build()))?;
+    writer.write(&tabela)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::estrutura::Dispositivo;
+    use crate::exportacao_parquet::escreve_leis_parquet;
+    use crate::parser::parse_html_to_lei;
+    use assert_fs::TempDir;
+    use parquet::basic::Encoding;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::fs::File;
+    use std::path::Path;
+
+    fn coluna(reader: &SerializedFileReader<File>, nome: &str) -> usize {
+        let schema = reader.metadata().file_metadata().schema_descr();
+        (0..schema.num_columns())
+            .find(|indice| schema.column(*indice).name() == nome)
+            .unwrap()
+    }
+
+    fn conta(dispositivos: &[Dispositivo]) -> i64 {
+        dispositivos
+            .iter()
+            .map(|dispositivo| 1 + conta(&dispositivo.filhos))
+            .sum()
+    }
+
+    fn le(caminho: &Path) -> SerializedFileReader<File> {
+        SerializedFileReader::new(File::open(caminho).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn should_write_leis_artigos_and_referencias_to_parquet() {
+        let leis = vec![
+            parse_html_to_lei(
+                "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+                vec!["complementar".to_string()],
+                None,
+            )
+            .unwrap(),
+            parse_html_to_lei(
+                "resources/integration_tests/leis/complementar/LeisMunicipais-com-br-Decreto-5907-1995.html",
+                vec!["complementar".to_string()],
+                None,
+            )
+            .unwrap(),
+        ];
+        let temp = TempDir::new().unwrap();
+        let caminho = temp.path().join("leis.parquet");
+        let caminho_dos_artigos = temp.path().join("leis-artigos.parquet");
+        let caminho_das_referencias = temp.path().join("leis-referencias.parquet");
+
+        escreve_leis_parquet(
+            &leis,
+            &caminho,
+            &caminho_dos_artigos,
+            &caminho_das_referencias,
+        )
+        .unwrap();
+
+        let reader = le(&caminho);
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let row_group = reader.metadata().row_group(0);
+        assert!(row_group
+            .column(coluna(&reader, "categoria"))
+            .encodings()
+            .iter()
+            .any(|encoding| matches!(
+                encoding,
+                Encoding::PLAIN_DICTIONARY | Encoding::RLE_DICTIONARY
+            )));
+        assert!(!row_group
+            .column(coluna(&reader, "texto"))
+            .encodings()
+            .iter()
+            .any(|encoding| matches!(
+                encoding,
+                Encoding::PLAIN_DICTIONARY | Encoding::RLE_DICTIONARY
+            )));
+
+        assert_eq!(
+            le(&caminho_dos_artigos)
+                .metadata()
+                .file_metadata()
+                .num_rows(),
+            conta(&leis[0].artigos) + conta(&leis[1].artigos)
+        );
+        assert_eq!(
+            le(&caminho_das_referencias)
+                .metadata()
+                .file_metadata()
+                .num_rows(),
+            9
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use crate::estrutura::Dispositivo;
+use crate::exportacao::nome_serializado;
+use crate::parser::Lei;
+use rusqlite::{params, Connection, Transaction, NO_PARAMS};
+use std::convert::TryFrom;
+use std::path::Path;
+
+// remove_diacritics faz "pensao" encontrar "pensão", como se espera de uma busca em português
+const ESQUEMA: &str = "
+CREATE TABLE leis (
+    id INTEGER PRIMARY KEY,
+    titulo TEXT NOT NULL,
+    tipo TEXT,
+    numero INTEGER,
+    ano INTEGER,
+    data TEXT,
+    categoria TEXT NOT NULL,
+    resumo TEXT NOT NULL,
+    texto TEXT NOT NULL,
+    texto_consolidado TEXT NOT NULL,
+    documento TEXT,
+    perfil TEXT NOT NULL,
+    codificacao TEXT NOT NULL
+);
+CREATE TABLE categorias (
+    lei_id INTEGER NOT NULL REFERENCES leis (id),
+    nivel INTEGER NOT NULL,
+    nome TEXT NOT NULL,
+    PRIMARY KEY (lei_id, nivel)
+);
+CREATE TABLE referencias (
+    id INTEGER PRIMARY KEY,
+    lei_id INTEGER NOT NULL REFERENCES leis (id),
+    norma_id INTEGER,
+    url TEXT,
+    tipo TEXT,
+    numero INTEGER,
+    ano INTEGER,
+    data TEXT,
+    ementa TEXT,
+    posicao INTEGER NOT NULL
+);
+CREATE TABLE artigos (
+    id INTEGER PRIMARY KEY,
+    lei_id INTEGER NOT NULL REFERENCES leis (id),
+    pai_id INTEGER REFERENCES artigos (id),
+    ordem INTEGER NOT NULL,
+    tipo TEXT NOT NULL,
+    rotulo TEXT NOT NULL,
+    texto TEXT NOT NULL
+);
+CREATE INDEX categorias_nome ON categorias (nome);
+CREATE INDEX referencias_lei_id ON referencias (lei_id);
+CREATE INDEX artigos_lei_id ON artigos (lei_id);
+CREATE VIRTUAL TABLE leis_fts USING fts5 (
+    titulo,
+    resumo,
+    texto,
+    content = 'leis',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+";
+
+pub fn escreve_leis_sqlite(leis: &[Lei], caminho: &Path) -> rusqlite::Result<()> {
+    let mut conexao = Connection::open(caminho)?;
+    conexao.execute_batch(ESQUEMA)?;
+
+    let transacao = conexao.transaction()?;
+    for lei in leis {
+        insere_lei(&transacao, lei)?;
+    }
+    transacao.execute(
+        "INSERT INTO leis_fts (leis_fts) VALUES ('rebuild')",
+        NO_PARAMS,
+    )?;
+    transacao.commit()
+}
+
+fn insere_lei(transacao: &Transaction, lei: &Lei) -> rusqlite::Result<()> {
+    let identificacao = &lei.identificacao;
+    transacao.execute(
+        "INSERT INTO leis (titulo, tipo, numero, ano, data, categoria, resumo, texto,
+            texto_consolidado, documento, perfil, codificacao)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            lei.titulo,
+            identificacao.tipo.as_ref().and_then(nome_serializado),
+            identificacao.numero,
+            identificacao.ano,
+            identificacao.data,
+            lei.categoria,
+            lei.resumo,
+            lei.texto,
+            lei.texto_consolidado,
+            lei.documento,
+            lei.perfil,
+            lei.codificacao,
+        ],
+    )?;
+    let lei_id = transacao.last_insert_rowid();
+
+    for (nivel, nome) in (0_i64..).zip(&lei.categorias) {
+        transacao.execute(
+            "INSERT INTO categorias (lei_id, nivel, nome) VALUES (?1, ?2, ?3)",
+            params![lei_id, nivel, nome],
+        )?;
+    }
+
+    for referencia in &lei.referencias {
+        transacao.execute(
+            "INSERT INTO referencias (lei_id, norma_id, url, tipo, numero, ano, data, ementa, posicao)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                lei_id,
+                referencia.id.and_then(|id| i64::try_from(id).ok()),
+                referencia.url,
+                referencia.tipo.as_ref().and_then(nome_serializado),
+                referencia.numero,
+                referencia.ano,
+                referencia.data,
+                referencia.ementa,
+                i64::try_from(referencia.posicao).unwrap_or(i64::MAX),
+            ],
+        )?;
+    }
+
+    insere_dispositivos(transacao, lei_id, None, &lei.artigos)
+}
+
+fn insere_dispositivos(
+    transacao: &Transaction,
+    lei_id: i64,
+    pai_id: Option<i64>,
+    dispositivos: &[Dispositivo],
+) -> rusqlite::Result<()> {
+    for (ordem, dispositivo) in (0_i64..).zip(dispositivos) {
+        transacao.execute(
+            "INSERT INTO artigos (lei_id, pai_id, ordem, tipo, rotulo, texto)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                lei_id,
+                pai_id,
+                ordem,
+                nome_serializado(&dispositivo.tipo),
+                dispositivo.rotulo,
+                dispositivo.texto,
+            ],
+        )?;
+        let id = transacao.last_insert_rowid();
+        insere_dispositivos(transacao, lei_id, Some(id), &dispositivo.filhos)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exportacao_sqlite::escreve_leis_sqlite;
+    use crate::parser::parse_html_to_lei;
+    use assert_fs::TempDir;
+    use rusqlite::{Connection, NO_PARAMS};
+
+    #[test]
+    fn should_write_leis_into_normalized_tables_with_full_text_search() {
+        let leis = vec![
+            parse_html_to_lei(
+                "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+                vec!["complementar".to_string()],
+                None,
+            )
+            .unwrap(),
+            parse_html_to_lei(
+                "resources/integration_tests/leis/complementar/LeisMunicipais-com-br-Decreto-5907-1995.html",
+                vec!["complementar".to_string(), "decretos".to_string()],
+                None,
+            )
+            .unwrap(),
+        ];
+        let temp = TempDir::new().unwrap();
+        let caminho = temp.path().join("leis.db");
+
+        escreve_leis_sqlite(&leis, &caminho).unwrap();
+
+        let conexao = Connection::open(&caminho).unwrap();
+        let conta = |sql: &str| -> i64 {
+            conexao
+                .query_row(sql, NO_PARAMS, |linha| linha.get(0))
+                .unwrap()
+        };
+        assert_eq!(conta("SELECT COUNT(*) FROM leis"), 2);
+        assert_eq!(conta("SELECT COUNT(*) FROM categorias"), 3);
+        assert_eq!(
+            conta("SELECT COUNT(*) FROM referencias WHERE lei_id = 1"),
+            9
+        );
+        assert_eq!(
+            conta("SELECT COUNT(*) FROM artigos WHERE lei_id = 2 AND pai_id IS NULL"),
+            8
+        );
+        assert_eq!(
+            conta("SELECT COUNT(*) FROM leis_fts WHERE leis_fts MATCH 'pensao'"),
+            1
+        );
+        assert_eq!(
+            conexao
+                .query_row("SELECT tipo FROM leis WHERE id = 1", NO_PARAMS, |linha| {
+                    linha.get::<_, String>(0)
+                })
+                .unwrap(),
+            "lei_complementar"
+        );
+    }
+}
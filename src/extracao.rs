@@ -0,0 +1,268 @@
+use crate::error::{CapturedOkOrUnexpected, Error};
+use crate::parser::PerfilDeLayout;
+use ego_tree::NodeRef;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+
+lazy_static! {
+    static ref TITULO_REGEX: Regex = Regex::new("<h2>(?P<titulo>(.*))</h2>").unwrap();
+    static ref RESUMO_REGEX: Regex = Regex::new("</h2><br>(?P<resumo>(.*))<br><br><img").unwrap();
+    static ref TEXTO_REGEX: Regex = Regex::new("><br><br><br>(?P<texto>(.*))<p><img").unwrap();
+    static ref DOCUMENTO_REGEX: Regex =
+        Regex::new("btn-default\" href=\"(?P<documento>(.*))\" title").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Fragmentos {
+    pub titulo: String,
+    pub resumo: String,
+    pub texto: String,
+    pub documento: Option<String>,
+}
+
+pub struct PerfilLeisMunicipais;
+
+impl PerfilDeLayout for PerfilLeisMunicipais {
+    fn nome(&self) -> &'static str {
+        "leismunicipais"
+    }
+
+    fn detecta(&self, html: &str) -> bool {
+        html.contains("span_text") && html.contains("<h2")
+    }
+
+    fn extrai(&self, html: &str, file_name: &str) -> Result<Fragmentos, Error> {
+        extrai_por_dom(html, file_name)
+    }
+}
+
+// primeiro layout suportado, serve de último recurso para qualquer arquivo
+pub struct PerfilLegado;
+
+impl PerfilDeLayout for PerfilLegado {
+    fn nome(&self) -> &'static str {
+        "legado"
+    }
+
+    fn detecta(&self, _html: &str) -> bool {
+        true
+    }
+
+    fn extrai(&self, html: &str, file_name: &str) -> Result<Fragmentos, Error> {
+        extrai_por_regex(html, file_name)
+    }
+}
+
+pub fn extrai_por_dom(html: &str, file_name: &str) -> Result<Fragmentos, Error> {
+    let documento_html = Html::parse_document(html);
+    let span_text = documento_html
+        .select(&selector("#span_text"))
+        .next()
+        .ok_or_unexpected("Título", file_name)?;
+    let titulo = span_text
+        .select(&selector("h2"))
+        .next()
+        .ok_or_unexpected("Título", file_name)?;
+
+    let nos = titulo.next_siblings().collect::<Vec<NodeRef<Node>>>();
+    let cabecalho = nos
+        .iter()
+        .position(|no| is_element(*no, "img"))
+        .ok_or_unexpected("Resumo", file_name)?;
+    let rodape = nos
+        .iter()
+        .rposition(|no| {
+            is_element(*no, "p") && no.descendants().any(|filho| is_element(filho, "img"))
+        })
+        .filter(|rodape| *rodape > cabecalho)
+        .unwrap_or_else(|| nos.len());
+
+    let resumo = serializa(sem_quebras_nas_pontas(&nos[..cabecalho]));
+    if resumo.is_empty() {
+        return None.ok_or_unexpected("Resumo", file_name);
+    }
+    let texto = serializa(sem_quebras_nas_pontas(&nos[cabecalho + 1..rodape]));
+    if texto.is_empty() {
+        return None.ok_or_unexpected("Texto", file_name);
+    }
+
+    Ok(Fragmentos {
+        titulo: titulo.inner_html(),
+        resumo,
+        texto,
+        documento: span_text
+            .select(&selector("a.btn-default"))
+            .next()
+            .and_then(|link| link.value().attr("href"))
+            .map(str::to_string),
+    })
+}
+
+pub fn extrai_por_regex(html: &str, file_name: &str) -> Result<Fragmentos, Error> {
+    let captures_titulo = TITULO_REGEX
+        .captures(html)
+        .ok_or_unexpected("Título", file_name)?;
+    let captures_resumo = RESUMO_REGEX
+        .captures(html)
+        .ok_or_unexpected("Resumo", file_name)?;
+    let captures_texto = TEXTO_REGEX
+        .captures(html)
+        .ok_or_unexpected("Texto", file_name)?;
+    let documento = DOCUMENTO_REGEX
+        .captures(html)
+        .map(|captures_documento| captures_documento["documento"].to_string());
+
+    Ok(Fragmentos {
+        titulo: captures_titulo["titulo"].to_string(),
+        resumo: captures_resumo["resumo"].to_string(),
+        texto: captures_texto["texto"].to_string(),
+        documento,
+    })
+}
+
+fn selector(seletor: &str) -> Selector {
+    Selector::parse(seletor).unwrap()
+}
+
+fn is_element(no: NodeRef<Node>, nome: &str) -> bool {
+    no.value()
+        .as_element()
+        .map_or(false, |elemento| elemento.name() == nome)
+}
+
+fn is_quebra(no: NodeRef<Node>) -> bool {
+    match no.value() {
+        Node::Text(texto) => texto.trim().is_empty(),
+        Node::Comment(_) => true,
+        _ => is_element(no, "br"),
+    }
+}
+
+fn sem_quebras_nas_pontas<'a, 'b>(nos: &'b [NodeRef<'a, Node>]) -> &'b [NodeRef<'a, Node>] {
+    let inicio = nos
+        .iter()
+        .position(|no| !is_quebra(*no))
+        .unwrap_or_else(|| nos.len());
+    let fim = nos
+        .iter()
+        .rposition(|no| !is_quebra(*no))
+        .map_or(inicio, |fim| fim + 1);
+    &nos[inicio..fim]
+}
+
+fn serializa(nos: &[NodeRef<Node>]) -> String {
+    nos.iter()
+        .map(|no| match no.value() {
+            Node::Text(texto) => texto
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;"),
+            Node::Element(_) => ElementRef::wrap(*no)
+                .map(|elemento| elemento.html())
+                .unwrap_or_default(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extracao::{extrai_por_dom, extrai_por_regex, PerfilLeisMunicipais};
+    use crate::parser::clean_html_to_text;
+    use crate::parser::PerfilDeLayout;
+    use encoding_rs::WINDOWS_1252;
+    use std::fs;
+
+    fn html(file_name: &str) -> String {
+        WINDOWS_1252
+            .decode(&fs::read(file_name).unwrap())
+            .0
+            .into_owned()
+    }
+
+    #[test]
+    fn should_extract_the_same_text_with_dom_and_regex() {
+        for file_name in &[
+            "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+            "resources/unit_tests/LeisMunicipais-com-br-Decreto-1-1984.html",
+        ] {
+            let html = html(file_name);
+            let dom = extrai_por_dom(&html, file_name).unwrap();
+            let regex = extrai_por_regex(&html, file_name).unwrap();
+
+            assert_eq!(
+                clean_html_to_text(&dom.titulo),
+                clean_html_to_text(&regex.titulo)
+            );
+            assert_eq!(
+                clean_html_to_text(&dom.resumo),
+                clean_html_to_text(&regex.resumo)
+            );
+            assert_eq!(
+                clean_html_to_text(&dom.texto),
+                clean_html_to_text(&regex.texto)
+            );
+            assert_eq!(dom.documento, regex.documento);
+        }
+    }
+
+    #[test]
+    fn should_extract_with_dom_when_br_tags_vary() {
+        let file_name = "resources/unit_tests/Leis_sem_resumo.html";
+        let fragmentos = extrai_por_dom(&html(file_name), file_name).unwrap();
+        assert!(clean_html_to_text(&fragmentos.resumo).starts_with("Altera as disposi"));
+
+        let file_name = "resources/unit_tests/Leis_sem_texto.html";
+        let fragmentos = extrai_por_dom(&html(file_name), file_name).unwrap();
+        assert!(clean_html_to_text(&fragmentos.texto).starts_with("O PREFEITO MUNICIPAL"));
+    }
+
+    #[test]
+    fn should_detect_leismunicipais_layout_only_when_span_text_has_h2() {
+        assert!(PerfilLeisMunicipais.detecta(&html(
+            "resources/unit_tests/LeisMunicipais-com-br-Decreto-1-1984.html"
+        )));
+        assert!(
+            !PerfilLeisMunicipais.detecta(&html("resources/unit_tests/Leis_sem_titulo_comh2.html"))
+        );
+    }
+
+    #[test]
+    fn should_return_pattern_not_found_error_when_there_is_no_h2() {
+        let file_name = "resources/unit_tests/Leis_sem_titulo_comh2.html";
+
+        assert_eq!(
+            &format!(
+                "{}",
+                extrai_por_dom(&html(file_name), file_name).unwrap_err()
+            ),
+            "Título não encontrado no arquivo resources/unit_tests/Leis_sem_titulo_comh2.html"
+        );
+    }
+
+    #[test]
+    fn should_return_pattern_not_found_error_when_resumo_pattern_not_found() {
+        let file_name = "resources/unit_tests/Leis_sem_resumo.html";
+
+        assert_eq!(
+            &format!(
+                "{}",
+                extrai_por_regex(&html(file_name), file_name).unwrap_err()
+            ),
+            "Resumo não encontrado no arquivo resources/unit_tests/Leis_sem_resumo.html"
+        );
+    }
+
+    #[test]
+    fn should_return_pattern_not_found_error_when_texto_pattern_not_found() {
+        let file_name = "resources/unit_tests/Leis_sem_texto.html";
+
+        assert_eq!(
+            &format!(
+                "{}",
+                extrai_por_regex(&html(file_name), file_name).unwrap_err()
+            ),
+            "Texto não encontrado no arquivo resources/unit_tests/Leis_sem_texto.html"
+        );
+    }
+}
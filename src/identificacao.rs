@@ -0,0 +1,276 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+lazy_static! {
+    static ref NORMA_REGEX: Regex = Regex::new(
+        r"(?i)^\s*(?P<tipo>.+?)\s+N\s*[º°o]\.?\s*(?P<numero>\d[\d.]*)(?:\s*/\s*(?P<ano>\d{2,4})\b)?"
+    )
+    .unwrap();
+    static ref DATA_POR_EXTENSO_REGEX: Regex =
+        Regex::new(r"(?i)(?P<dia>\d{1,2})\s*[º°]?\s+de\s+(?P<mes>\p{L}+)\s+de\s+(?P<ano>\d{4})")
+            .unwrap();
+    static ref DATA_NUMERICA_REGEX: Regex =
+        Regex::new(r"(?P<dia>\d{1,2})[./](?P<mes>\d{1,2})[./](?P<ano>\d{4})").unwrap();
+}
+
+const MESES: [&str; 12] = [
+    "JANEIRO",
+    "FEVEREIRO",
+    "MARCO",
+    "ABRIL",
+    "MAIO",
+    "JUNHO",
+    "JULHO",
+    "AGOSTO",
+    "SETEMBRO",
+    "OUTUBRO",
+    "NOVEMBRO",
+    "DEZEMBRO",
+];
+
+/// Tipo da norma, identificado no título da lei.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TipoNorma {
+    Lei,
+    LeiComplementar,
+    LeiOrganica,
+    EmendaLeiOrganica,
+    Decreto,
+    DecretoLegislativo,
+    Resolucao,
+    Portaria,
+}
+
+impl TipoNorma {
+    pub(crate) fn from_descricao(descricao: &str) -> Option<TipoNorma> {
+        let descricao = remove_acentos(&descricao.trim().to_uppercase());
+        let tipo = if descricao.starts_with("EMENDA") {
+            TipoNorma::EmendaLeiOrganica
+        } else if descricao.starts_with("LEI COMPLEMENTAR") {
+            TipoNorma::LeiComplementar
+        } else if descricao.starts_with("LEI ORGANICA") {
+            TipoNorma::LeiOrganica
+        } else if descricao.starts_with("LEI") {
+            TipoNorma::Lei
+        } else if descricao.starts_with("DECRETO LEGISLATIVO") {
+            TipoNorma::DecretoLegislativo
+        } else if descricao.starts_with("DECRETO") {
+            TipoNorma::Decreto
+        } else if descricao.starts_with("RESOLUCAO") {
+            TipoNorma::Resolucao
+        } else if descricao.starts_with("PORTARIA") {
+            TipoNorma::Portaria
+        } else {
+            return None;
+        };
+        Some(tipo)
+    }
+}
+
+impl fmt::Display for TipoNorma {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let descricao = match self {
+            TipoNorma::Lei => "Lei",
+            TipoNorma::LeiComplementar => "Lei Complementar",
+            TipoNorma::LeiOrganica => "Lei Orgânica",
+            TipoNorma::EmendaLeiOrganica => "Emenda à Lei Orgânica",
+            TipoNorma::Decreto => "Decreto",
+            TipoNorma::DecretoLegislativo => "Decreto Legislativo",
+            TipoNorma::Resolucao => "Resolução",
+            TipoNorma::Portaria => "Portaria",
+        };
+        write!(f, "{}", descricao)
+    }
+}
+
+/// Tipo, número, ano e data da norma, quando encontrados no título da lei.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Identificacao {
+    /// Tipo da norma, como lei complementar ou decreto.
+    pub tipo: Option<TipoNorma>,
+    /// Número da norma.
+    pub numero: Option<u32>,
+    /// Ano da norma, da data ou do número no formato `5907/1995`.
+    pub ano: Option<i32>,
+    /// Data da norma no formato ISO 8601, como `2019-02-22`.
+    pub data: Option<String>,
+}
+
+pub fn parse_titulo(titulo: &str) -> Identificacao {
+    let data = parse_data(titulo);
+    let ano_da_data = data.map(|(ano, _, _)| ano);
+
+    let (tipo, numero, ano) = match NORMA_REGEX.captures(titulo) {
+        Some(captures) => {
+            let tipo = TipoNorma::from_descricao(&captures["tipo"]);
+            let numero = captures["numero"].replace('.', "").parse::<u32>().ok();
+            let ano = captures
+                .name("ano")
+                .and_then(|ano| normaliza_ano(ano.as_str(), ano_da_data))
+                .or(ano_da_data);
+            (tipo, numero, ano)
+        }
+        None => (None, None, ano_da_data),
+    };
+
+    Identificacao {
+        tipo,
+        numero,
+        ano,
+        data: data.map(formata_data),
+    }
+}
+
+pub fn data_iso(texto: &str) -> Option<String> {
+    parse_data(texto).map(formata_data)
+}
+
+fn formata_data((ano, mes, dia): (i32, u32, u32)) -> String {
+    format!("{:04}-{:02}-{:02}", ano, mes, dia)
+}
+
+fn parse_data(texto: &str) -> Option<(i32, u32, u32)> {
+    let (ano, mes, dia) = if let Some(captures) = DATA_POR_EXTENSO_REGEX.captures(texto) {
+        (
+            captures["ano"].parse().ok()?,
+            mes_por_extenso(&captures["mes"])?,
+            captures["dia"].parse().ok()?,
+        )
+    } else {
+        let captures = DATA_NUMERICA_REGEX.captures(texto)?;
+        (
+            captures["ano"].parse().ok()?,
+            captures["mes"].parse().ok()?,
+            captures["dia"].parse().ok()?,
+        )
+    };
+
+    if (1..=12).contains(&mes) && (1..=dias_do_mes(ano, mes)).contains(&dia) {
+        Some((ano, mes, dia))
+    } else {
+        None
+    }
+}
+
+fn dias_do_mes(ano: i32, mes: u32) -> u32 {
+    match mes {
+        2 if ano % 4 == 0 && (ano % 100 != 0 || ano % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn mes_por_extenso(mes: &str) -> Option<u32> {
+    let mes = remove_acentos(&mes.to_uppercase());
+    (1..=12)
+        .zip(MESES.iter())
+        .find(|(_, nome)| **nome == mes)
+        .map(|(numero, _)| numero)
+}
+
+fn normaliza_ano(ano: &str, ano_da_data: Option<i32>) -> Option<i32> {
+    let valor = ano.parse::<i32>().ok()?;
+    if ano.len() == 4 {
+        return Some(valor);
+    }
+
+    match ano_da_data {
+        Some(ano_da_data) if ano_da_data % 100 == valor => Some(ano_da_data),
+        _ if valor > 30 => Some(1900 + valor),
+        _ => Some(2000 + valor),
+    }
+}
+
+pub fn remove_acentos(texto: &str) -> String {
+    texto
+        .chars()
+        .map(|c| match c {
+            'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'É' | 'È' | 'Ê' | 'Ë' => 'E',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'Ç' => 'C',
+            'ç' => 'c',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::identificacao::{data_iso, parse_titulo, Identificacao, TipoNorma};
+
+    #[test]
+    fn should_parse_lei_complementar_titulo() {
+        assert_eq!(
+            parse_titulo("LEI COMPLEMENTAR Nº 122, DE 22 DE FEVEREIRO DE 2019"),
+            Identificacao {
+                tipo: Some(TipoNorma::LeiComplementar),
+                numero: Some(122),
+                ano: Some(2019),
+                data: Some("2019-02-22".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn should_normalize_two_digit_ano() {
+        assert_eq!(
+            parse_titulo("DECRETO Nº 1/84, de 05 de janeiro de 1984"),
+            Identificacao {
+                tipo: Some(TipoNorma::Decreto),
+                numero: Some(1),
+                ano: Some(1984),
+                data: Some("1984-01-05".to_string()),
+            }
+        );
+        assert_eq!(parse_titulo("RESOLUÇÃO Nº 12/03").ano, Some(2003));
+    }
+
+    #[test]
+    fn should_parse_titulo_with_accents_and_thousands_separator() {
+        assert_eq!(
+            parse_titulo("EMENDA À LEI ORGÂNICA Nº 1.802, DE 1º DE MARÇO DE 1995"),
+            Identificacao {
+                tipo: Some(TipoNorma::EmendaLeiOrganica),
+                numero: Some(1802),
+                ano: Some(1995),
+                data: Some("1995-03-01".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_days_beyond_the_end_of_the_month() {
+        assert_eq!(data_iso("31 de fevereiro de 2019"), None);
+        assert_eq!(data_iso("29 de fevereiro de 2019"), None);
+        assert_eq!(
+            data_iso("29 de fevereiro de 2020"),
+            Some("2020-02-29".to_string())
+        );
+        assert_eq!(data_iso("29/02/1900"), None);
+        assert_eq!(data_iso("29/02/2000"), Some("2000-02-29".to_string()));
+        assert_eq!(data_iso("31/04/1995"), None);
+        assert_eq!(data_iso("30/04/1995"), Some("1995-04-30".to_string()));
+        assert_eq!(
+            parse_titulo("DECRETO Nº 7, DE 31 DE ABRIL DE 1995").data,
+            None
+        );
+    }
+
+    #[test]
+    fn should_return_empty_identificacao_when_titulo_is_unknown() {
+        assert_eq!(parse_titulo("ATA DA SESSÃO"), Identificacao::default());
+    }
+}
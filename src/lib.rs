@@ -0,0 +1,61 @@
+//! Parser dos arquivos HTML disponibilizados pelo
+//! [Leis Municipais](https://www.leismunicipais.com.br).
+//!
+//! Um arquivo é parseado com o [`Parser`], e uma pasta inteira (ou um arquivo `.zip`, `.tar`
+//! ou `.tar.gz` com os arquivos HTML) com [`parse_on_directory`], que usa as subpastas como
+//! categorias das leis. As leis podem então ser gravadas em vários formatos pelo módulo
+//! [`exportacao`], publicadas como um site estático pelo módulo [`site`], pesquisadas com o
+//! índice do módulo [`busca`] ou consultadas por uma API HTTP local com o módulo [`servidor`].
+//!
+//! ```
+//! use leis_municipais::{parse_on_directory, Configuracao, TipoNorma, Verbosidade};
+//!
+//! let configuracao = Configuracao::new().verbosidade(Verbosidade::Silencioso);
+//! let (pastas, leis) = parse_on_directory("resources/integration_tests/leis", configuracao);
+//!
+//! assert_eq!(pastas["complementar"].total, 2);
+//! let decretos = leis
+//!     .iter()
+//!     .filter(|lei| lei.identificacao().tipo == Some(TipoNorma::Decreto))
+//!     .count();
+//! assert_eq!(decretos, 2);
+//! ```
+#![allow(clippy::non_ascii_literal)]
+#[macro_use]
+extern crate lazy_static;
+
+pub mod busca;
+mod cache;
+mod codificacao;
+mod compactado;
+mod error;
+mod estrutura;
+pub mod exportacao;
+mod exportacao_akn;
+mod exportacao_csv;
+mod exportacao_lexml;
+mod exportacao_markdown;
+mod exportacao_parquet;
+mod exportacao_sqlite;
+mod extracao;
+mod identificacao;
+mod parser;
+mod parser_executor;
+mod radical;
+mod referencias;
+pub mod servidor;
+pub mod site;
+mod vigencia;
+mod xml;
+
+pub use crate::cache::Cache;
+pub use crate::error::Error;
+pub use crate::estrutura::{Dispositivo, TipoDispositivo};
+pub use crate::identificacao::{Identificacao, TipoNorma};
+pub use crate::parser::{Lei, Parser, VERSAO_DO_PARSER};
+pub use crate::parser_executor::{
+    parse_on_directory, parse_on_directory_to, Configuracao, Folder, Verbosidade,
+};
+pub use crate::referencias::Referencia;
+pub use crate::vigencia::{Situacao, Trecho};
+pub use encoding_rs::Encoding;
@@ -1,63 +1,340 @@
 #![allow(clippy::non_ascii_literal)]
 #[macro_use]
 extern crate prettytable;
-#[macro_use]
-extern crate lazy_static;
 
-use crate::error::Error;
-use crate::parser::Lei;
-use crate::parser_executor::{parse_on_directory, Folder};
+use crate::cli::{Comando, Opcoes};
+use leis_municipais::busca::{Indice, Origem, Resultado};
+use leis_municipais::exportacao::{
+    caminho_relacionado, e_saida_padrao, escreve_em_fluxo, escreve_leis, escreve_resumo_das_pastas,
+    le_leis, Formato,
+};
+use leis_municipais::servidor::Api;
+use leis_municipais::site::escreve_paginas;
+use leis_municipais::{
+    parse_on_directory, parse_on_directory_to, Cache, Configuracao, Error, Folder, Lei, Verbosidade,
+};
 use prettytable::Table;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
-use std::fs::File;
+use std::ffi::OsStr;
+use std::panic;
+use std::path::Path;
+use std::process;
 use std::time::Instant;
+use structopt::StructOpt;
 
-mod error;
-mod parser;
-mod parser_executor;
+mod cli;
 
 fn main() -> Result<(), Error> {
     let now = Instant::now();
-    let args: Vec<String> = env::args().collect();
-    let directory_path = &args[1]; // TODO: error handler
+    let opcoes = Opcoes::from_args();
+    if let Some(threads) = opcoes.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("Não foi possível configurar as threads");
+    }
+    let mut cache = match &opcoes.cache {
+        Some(caminho) => Some(Cache::abre(caminho, opcoes.encoding)?),
+        None => None,
+    };
+    let verbosidade = opcoes.verbosidade();
+    let mut configuracao = Configuracao::new().verbosidade(verbosidade);
+    if let Some(codificacao) = opcoes.encoding {
+        configuracao = configuracao.codificacao(codificacao);
+    }
+    if let Some(cache) = &cache {
+        configuracao = configuracao.cache(cache);
+    }
+    let com_cache = cache.is_some();
+    let mut falhou = false;
+    let silencioso = verbosidade == Verbosidade::Silencioso || opcoes.comando.usa_saida_padrao();
+
+    match opcoes.comando {
+        Comando::Parse { diretorio, saida } => {
+            let output = saida.caminho();
+            let opcoes_de_exportacao = saida.opcoes();
+            let directories = if saida.format.em_fluxo() {
+                let (sender, escritor) = escreve_em_fluxo(&output)?;
+                let directories = parse_on_directory_to(&diretorio, configuracao, sender);
+                // o erro de gravação, como um pipe fechado ou o disco cheio, interrompe o parse
+                // e é devolvido pela thread de escrita
+                escritor
+                    .join()
+                    .unwrap_or_else(|panico| panic::resume_unwind(panico))?;
+                directories
+            } else {
+                let (directories, leis) = parse_on_directory(&diretorio, configuracao);
+                let gravadas = escreve_leis(&leis, saida.format, &output, &opcoes_de_exportacao)?;
+                if !silencioso {
+                    print_left_out(leis.len() - gravadas);
+                }
+                directories
+            };
+            if saida.format == Formato::Csv && !e_saida_padrao(&output) {
+                let resumo = caminho_relacionado(&output, "pastas");
+                escreve_resumo_das_pastas(&directories, &resumo, &opcoes_de_exportacao)?;
+            }
+            if !silencioso {
+                print_report(&directories, com_cache);
+                print_output_path(&output);
+            }
+        }
+        Comando::Validate { diretorio } => {
+            let (directories, _) = parse_on_directory(&diretorio, configuracao);
+            if !silencioso {
+                print_report(&directories, com_cache);
+                print_failed_files(&directories);
+            }
+            falhou = directories.values().any(|folder| !folder.failed.is_empty());
+        }
+        Comando::Stats { diretorio } => {
+            let (directories, leis) = parse_on_directory(&diretorio, configuracao);
+            print_report(&directories, com_cache);
+            print_stats(&leis);
+        }
+        Comando::Export {
+            arquivo,
+            entrada,
+            saida,
+        } => {
+            let leis = le_leis(&entrada)?;
+            let output = saida.caminho_ou(arquivo.as_ref());
+            let gravadas = escreve_leis(&leis, saida.format, &output, &saida.opcoes())?;
+            if !silencioso {
+                print_left_out(leis.len() - gravadas);
+                print_output_path(&output);
+            }
+        }
+        Comando::Site { saida, entrada } => {
+            let leis = le_leis(&entrada)?;
+            escreve_paginas(&leis, &saida)?;
+            if !silencioso {
+                print_output_path(&saida.join("index.html"));
+            }
+        }
+        Comando::Serve { entrada, porta } => {
+            let leis = carrega_leis(&entrada, configuracao, silencioso, com_cache)?;
+            // o servidor só para quando o processo é encerrado, então o cache é gravado antes
+            if let Some(cache) = cache.take() {
+                cache.grava()?;
+            }
+            let endereco = format!("127.0.0.1:{}", porta);
+            if !silencioso {
+                println!("\nServindo {} leis em http://{}", leis.len(), endereco);
+            }
+            Api::new(leis).serve(&endereco)?;
+        }
+        Comando::Search {
+            consulta,
+            indice: caminho_do_indice,
+            entrada,
+            limite,
+        } => {
+            // o índice gravado é refeito quando a entrada muda; sem a entrada, ele é usado como está
+            let origem = Origem::le(Path::new(&entrada)).ok();
+            let gravado = if caminho_do_indice.exists() {
+                Some(Indice::abre(&caminho_do_indice)?)
+            } else {
+                None
+            };
+            let indice = match gravado {
+                Some(indice) if origem.is_none() || indice.origem() == origem.as_ref() => {
+                    if !silencioso {
+                        print_index_path(&caminho_do_indice, &indice, false);
+                    }
+                    indice
+                }
+                _ => {
+                    let leis = carrega_leis(&entrada, configuracao, silencioso, com_cache)?;
+                    let mut indice = Indice::new(&leis);
+                    if let Some(origem) = origem {
+                        indice = indice.com_origem(origem);
+                    }
+                    indice.grava(&caminho_do_indice)?;
+                    if !silencioso {
+                        print_index_path(&caminho_do_indice, &indice, true);
+                    }
+                    indice
+                }
+            };
+            let busca = indice.busca(&consulta.join(" "), 0, limite);
+            print_results(&busca.resultados, busca.total);
+        }
+    }
+
+    if let Some(cache) = cache {
+        cache.grava()?;
+    }
+    if falhou {
+        process::exit(1);
+    }
+    if !silencioso {
+        println!("Tempo de execução: {} segundos", now.elapsed().as_secs());
+    }
+    Ok(())
+}
+
+fn carrega_leis(
+    entrada: &str,
+    configuracao: Configuracao,
+    silencioso: bool,
+    com_cache: bool,
+) -> Result<Vec<Lei>, Error> {
+    if e_arquivo_de_leis(entrada) {
+        return le_leis(Path::new(entrada));
+    }
+    let (directories, leis) = parse_on_directory(entrada, configuracao);
+    if !silencioso {
+        print_report(&directories, com_cache);
+    }
+    Ok(leis)
+}
 
-    let (directories, leis) = parse_on_directory(directory_path);
+fn e_arquivo_de_leis(entrada: &str) -> bool {
+    let extensao = Path::new(entrada).extension().and_then(OsStr::to_str);
+    matches!(extensao, Some("json" | "jsonl" | "ndjson"))
+}
 
+fn print_output_path(caminho: &Path) {
+    let caminho = if caminho.is_absolute() {
+        caminho.to_path_buf()
+    } else {
+        env::current_dir().unwrap().join(caminho)
+    };
+    println!("\nArquivo salvo em: {}", caminho.display());
+}
+
+fn print_left_out(quantidade: usize) {
+    match quantidade {
+        0 => {}
+        1 => println!("\n1 lei sem tipo, número e data identificados ficou de fora"),
+        _ => println!(
+            "\n{} leis sem tipo, número e data identificados ficaram de fora",
+            quantidade
+        ),
+    }
+}
+
+fn print_report(directories: &HashMap<String, Folder>, com_cache: bool) {
     let total_files = directories
         .iter()
         .map(|(_, folder)| folder.total)
         .sum::<i32>();
     println!("\nTotal de arquivos: {}", total_files);
-    print_report(&directories);
-    write_json_file(&leis);
+    if com_cache {
+        let cached = directories
+            .values()
+            .map(|folder| folder.cached)
+            .sum::<i32>();
+        println!(
+            "Do cache: {}, parseados novamente: {}",
+            cached,
+            total_files - cached
+        );
+    }
 
-    println!("Tempo de execução: {} segundos", now.elapsed().as_secs());
-    Ok(())
+    let mut table = Table::new();
+    if com_cache {
+        table.set_titles(row![
+            "Diretório",
+            "Total",
+            "Parseados",
+            "Do cache",
+            "Com erros",
+        ]);
+    } else {
+        table.set_titles(row!["Diretório", "Total", "Parseados", "Com erros",]);
+    }
+
+    for (directory, folder) in directories {
+        if com_cache {
+            table.add_row(row![
+                directory,
+                folder.total,
+                folder.parsed,
+                folder.cached,
+                folder.failed.len()
+            ]);
+        } else {
+            table.add_row(row![
+                directory,
+                folder.total,
+                folder.parsed,
+                folder.failed.len()
+            ]);
+        }
+    }
+
+    println!("\nResumo da execução:");
+    table.printstd();
 }
 
-fn write_json_file(leis: &[Lei]) {
-    let leis_file = File::create("leis.json").expect("Unable to create file");
-    serde_json::to_writer_pretty(leis_file, &leis).expect("Unable to write data");
-    println!(
-        "\nArquivo salvo em: {}",
-        format!("{}/leis.json", env::current_dir().unwrap().display())
-    );
+fn print_failed_files(directories: &HashMap<String, Folder>) {
+    let mut failed = directories
+        .values()
+        .flat_map(|folder| folder.failed.iter())
+        .collect::<Vec<&String>>();
+    if failed.is_empty() {
+        println!("\nTodos os arquivos foram parseados com sucesso.");
+        return;
+    }
+
+    failed.sort();
+    println!("\nArquivos com erros:");
+    for file_path in failed {
+        println!("{}", file_path);
+    }
 }
 
-fn print_report(directories: &HashMap<String, Folder>) {
-    let mut table = Table::new();
-    table.set_titles(row!["Diretório", "Total", "Parseados", "Com erros",]);
+fn print_index_path(caminho: &Path, indice: &Indice, criado: bool) {
+    let origem = indice.origem().map_or_else(String::new, |origem| {
+        format!(" a partir de {}", origem.caminho)
+    });
+    if criado {
+        println!("Índice criado em {}{}\n", caminho.display(), origem);
+    } else {
+        println!("Usando o índice {}, criado{}\n", caminho.display(), origem);
+    }
+}
 
-    for (directory, folder) in directories {
-        table.add_row(row![
-            directory,
-            folder.total,
-            folder.parsed,
-            (folder.total - folder.parsed)
-        ]);
+fn print_results(resultados: &[Resultado], total: usize) {
+    if resultados.is_empty() {
+        println!("Nenhuma lei encontrada.");
+        return;
     }
 
-    println!("\nResumo da execução:");
+    if total == 1 {
+        println!("1 lei encontrada");
+    } else {
+        println!("{} leis encontradas", total);
+    }
+    for (posicao, resultado) in (1..).zip(resultados) {
+        println!(
+            "\n{}. {} ({}, pontuação {:.2})",
+            posicao, resultado.titulo, resultado.id, resultado.pontuacao
+        );
+        println!("   {}", resultado.trecho);
+    }
+}
+
+fn print_stats(leis: &[Lei]) {
+    let mut por_tipo = BTreeMap::new();
+    for lei in leis {
+        let tipo = lei
+            .identificacao()
+            .tipo
+            .as_ref()
+            .map_or_else(|| "Não identificado".to_string(), ToString::to_string);
+        *por_tipo.entry(tipo).or_insert(0) += 1;
+    }
+
+    let mut table = Table::new();
+    table.set_titles(row!["Tipo de norma", "Leis"]);
+    for (tipo, total) in por_tipo {
+        table.add_row(row![tipo, total]);
+    }
+
+    println!("\nLeis por tipo de norma:");
     table.printstd();
 }
@@ -1,54 +1,390 @@
-use crate::parser::{parse_html_to_lei, Lei};
+use crate::cache::{impressao_digital, Cache};
+use crate::compactado::{e_compactado, percorre, Entrada};
+use crate::error::Error;
+use crate::parser::{parse_bytes_to_lei, parse_html_to_lei, Lei};
+use encoding_rs::Encoding;
 use rayon::prelude::*;
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread;
 use walkdir::{DirEntry, WalkDir};
 
+// limita quantos arquivos lidos do arquivo compactado ficam em memória esperando o parse
+const ARQUIVOS_EM_MEMORIA: usize = 64;
+
+/// Quanto o parse de uma pasta escreve na saída de erro.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Verbosidade {
+    /// Não escreve nada.
+    Silencioso,
+    /// Escreve os erros de cada arquivo.
+    Normal,
+    /// Escreve os erros e também cada arquivo parseado com sucesso.
+    Detalhado,
+}
+
+impl Default for Verbosidade {
+    fn default() -> Self {
+        Verbosidade::Normal
+    }
+}
+
+/// Opções do parse de uma pasta com [`parse_on_directory`] e [`parse_on_directory_to`].
+///
+/// ```
+/// use leis_municipais::{parse_on_directory, Cache, Configuracao, Verbosidade};
+///
+/// let caminho = std::env::temp_dir().join("leis-municipais-doc-cache.json");
+/// let cache = Cache::abre(&caminho, None)?;
+/// let configuracao = Configuracao::new()
+///     .verbosidade(Verbosidade::Silencioso)
+///     .cache(&cache);
+///
+/// let (pastas, leis) = parse_on_directory("resources/integration_tests/leis", configuracao);
+/// cache.grava()?;
+///
+/// assert_eq!(leis.len(), 3);
+/// assert_eq!(pastas["complementar"].parsed, 2);
+/// # Ok::<(), leis_municipais::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Configuracao<'a> {
+    pub(crate) codificacao: Option<&'static Encoding>,
+    pub(crate) verbosidade: Verbosidade,
+    pub(crate) cache: Option<&'a Cache>,
+}
+
+impl<'a> Configuracao<'a> {
+    /// Detecta a codificação de cada arquivo, exibe os erros e não usa cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Configuracao::default()
+    }
+
+    /// Força a codificação de todos os arquivos, como em
+    /// [`Parser::codificacao`](crate::Parser::codificacao).
+    #[must_use]
+    pub fn codificacao(mut self, codificacao: &'static Encoding) -> Self {
+        self.codificacao = Some(codificacao);
+        self
+    }
+
+    /// Define o que é escrito na saída de erro durante o parse.
+    #[must_use]
+    pub fn verbosidade(mut self, verbosidade: Verbosidade) -> Self {
+        self.verbosidade = verbosidade;
+        self
+    }
+
+    /// Reaproveita do cache as leis dos arquivos que não mudaram desde a última execução.
+    #[must_use]
+    pub fn cache(mut self, cache: &'a Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+}
+
+/// Resumo do parse dos arquivos de uma pasta.
+#[derive(Debug, Default, PartialEq)]
+#[non_exhaustive]
 pub struct Folder {
+    /// Arquivos HTML encontrados.
     pub total: i32,
+    /// Arquivos parseados com sucesso, incluindo os reaproveitados do cache.
     pub parsed: i32,
+    /// Arquivos reaproveitados do cache.
+    pub cached: i32,
+    /// Caminhos dos arquivos que não puderam ser parseados.
+    pub failed: Vec<String>,
 }
 
-pub fn parse_on_directory(directory_path: &str) -> (HashMap<String, Folder>, Vec<Lei>) {
+impl Folder {
+    fn add<T>(&mut self, file_path: &str, lei_result: &Result<T, Error>, do_cache: bool) {
+        self.total += 1;
+        match lei_result {
+            Ok(_) => self.parsed += 1,
+            Err(_) => self.failed.push(file_path.to_string()),
+        }
+        if do_cache {
+            self.cached += 1;
+        }
+    }
+}
+
+/// Parseia todos os arquivos HTML da pasta e das subpastas, em paralelo.
+///
+/// A pasta também pode ser um arquivo `.zip`, `.tar` ou `.tar.gz`, lido sem extração. As
+/// subpastas viram as categorias das leis, e o resumo é agrupado por elas.
+#[must_use]
+pub fn parse_on_directory(
+    directory_path: &str,
+    configuracao: Configuracao,
+) -> (HashMap<String, Folder>, Vec<Lei>) {
+    let results = parse_files(
+        directory_path,
+        configuracao,
+        &AtomicBool::new(false),
+        (),
+        |_, lei_result| lei_result,
+    );
+
+    group_by_folder(results, directory_path)
+}
+
+/// Como [`parse_on_directory`], mas envia cada lei ao canal assim que ela é parseada, sem
+/// acumular o resultado em memória. O canal precisa ser consumido em outra thread enquanto o
+/// parse acontece, já que o envio espera quando ele está cheio.
+///
+/// Se o [`Receiver`](std::sync::mpsc::Receiver) do canal for descartado antes do fim, como
+/// quando o escritor das leis falha, os arquivos restantes não são parseados nem contados.
+#[must_use]
+pub fn parse_on_directory_to(
+    directory_path: &str,
+    configuracao: Configuracao,
+    sender: SyncSender<Lei>,
+) -> HashMap<String, Folder> {
+    let interrompido = AtomicBool::new(false);
+    let results = parse_files(
+        directory_path,
+        configuracao,
+        &interrompido,
+        sender,
+        |sender, lei_result| {
+            lei_result.map(|lei| {
+                if sender.send(lei).is_err() {
+                    interrompido.store(true, Ordering::Relaxed);
+                }
+            })
+        },
+    );
+
+    group_by_folder(results, directory_path).0
+}
+
+// o diretório pode ser também um arquivo .zip, .tar ou .tar.gz, lido sem extração
+// os arquivos ainda não parseados são ignorados depois que `interrompido` é marcado
+fn parse_files<S, T, F>(
+    directory_path: &str,
+    configuracao: Configuracao,
+    interrompido: &AtomicBool,
+    estado: S,
+    trata: F,
+) -> Vec<(String, Result<T, Error>, bool)>
+where
+    S: Clone + Send,
+    T: Send,
+    F: Fn(&mut S, Result<Lei, Error>) -> Result<T, Error> + Send + Sync,
+{
+    if let Some(cache) = configuracao.cache {
+        cache.percorre(directory_path);
+    }
+    if e_compactado(directory_path) {
+        return parse_archive(directory_path, configuracao, interrompido, estado, trata);
+    }
+
+    let (files, walk_errors) = list_files(directory_path, configuracao);
+    files
+        .into_par_iter()
+        .filter(|_| !interrompido.load(Ordering::Relaxed))
+        .map_with(estado, |estado, (file_path, categorias)| {
+            let (lei_result, do_cache) = parse_file(&file_path, categorias, None, configuracao);
+            (file_path, trata(estado, lei_result), do_cache)
+        })
+        .chain(
+            walk_errors
+                .into_par_iter()
+                .map(|(path, erro)| (path, Err(erro), false)),
+        )
+        .collect()
+}
+
+fn parse_archive<S, T, F>(
+    archive_path: &str,
+    configuracao: Configuracao,
+    interrompido: &AtomicBool,
+    estado: S,
+    trata: F,
+) -> Vec<(String, Result<T, Error>, bool)>
+where
+    S: Clone + Send,
+    T: Send,
+    F: Fn(&mut S, Result<Lei, Error>) -> Result<T, Error> + Send + Sync,
+{
+    let (entradas, receiver) = mpsc::sync_channel(ARQUIVOS_EM_MEMORIA);
+    let caminho = archive_path.to_string();
+    let leitor = thread::spawn(move || {
+        percorre(&caminho, &mut |entrada| {
+            // o envio só falha se o parse já tiver sido interrompido por outra thread
+            let _ = entradas.send(entrada);
+        })
+    });
+
+    let mut results = receiver
+        .into_iter()
+        .par_bridge()
+        .filter(|_| !interrompido.load(Ordering::Relaxed))
+        .map_with(estado, |estado, entrada: Entrada| {
+            let (lei_result, do_cache) = match entrada.conteudo {
+                Ok(conteudo) => parse_file(
+                    &entrada.caminho,
+                    entrada.categorias,
+                    Some(conteudo),
+                    configuracao,
+                ),
+                Err(erro) => (Err(erro), false),
+            };
+            (entrada.caminho, trata(estado, lei_result), do_cache)
+        })
+        .collect::<Vec<(String, Result<T, Error>, bool)>>();
+    // o par_bridge não preserva a ordem em que as entradas foram lidas
+    results.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    if let Err(erro) = leitor
+        .join()
+        .expect("A thread de leitura do arquivo compactado falhou")
+    {
+        if configuracao.verbosidade != Verbosidade::Silencioso {
+            eprintln!("{}", erro);
+        }
+        results.push((archive_path.to_string(), Err(erro), false));
+    }
+    results
+}
+
+fn list_files(
+    directory_path: &str,
+    configuracao: Configuracao,
+) -> (Vec<(String, Vec<String>)>, Vec<(String, Error)>) {
+    let mut walk_errors = Vec::new();
     let walker = WalkDir::new(directory_path).into_iter();
-    let directories = HashMap::new();
     let files = walker
         .filter_entry(|entry| is_not_hidden(entry))
-        .skip(1)
-        .into_iter()
-        .filter_map(|entry| {
-            let entry = entry.unwrap();
-            if is_html_file(&entry) {
-                Some(
-                    entry
-                        .path()
-                        .to_str()
-                        .expect("file path not found")
-                        .to_string()
-                        .clone(),
-                )
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<String>>();
-
-    let leis = files
-        .par_iter()
-        .filter_map(|file_path| {
-            let lei_result = parse_html_to_lei(file_path, "".to_string());
-            match lei_result {
-                Ok(lei) => Some(lei),
-                Err(e) => {
-                    eprintln!("{}", e);
-                    None
+        .filter_map(|entry| match entry {
+            Ok(entry) if entry.depth() > 0 && is_html_file(&entry) => Some((
+                entry.path().to_string_lossy().to_string(),
+                categorias_from_path(entry.path(), directory_path),
+            )),
+            Ok(_) => None,
+            Err(erro) => {
+                let path = erro.path().map_or_else(
+                    || directory_path.to_string(),
+                    |path| path.to_string_lossy().to_string(),
+                );
+                let erro = Error::Diretorio(path.clone(), erro);
+                if configuracao.verbosidade != Verbosidade::Silencioso {
+                    eprintln!("{}", erro);
                 }
+                walk_errors.push((path, erro));
+                None
             }
         })
         .collect();
 
+    (files, walk_errors)
+}
+
+// o booleano indica se a lei foi reaproveitada do cache
+fn parse_file(
+    file_path: &str,
+    categorias: Vec<String>,
+    conteudo: Option<Vec<u8>>,
+    configuracao: Configuracao,
+) -> (Result<Lei, Error>, bool) {
+    let (lei_result, do_cache) = match (conteudo, configuracao.cache) {
+        (None, None) => (
+            parse_html_to_lei(file_path, categorias, configuracao.codificacao),
+            false,
+        ),
+        (conteudo, _) => match conteudo.map_or_else(|| fs::read(file_path), Ok) {
+            Ok(bytes) => parse_bytes(file_path, categorias, &bytes, configuracao),
+            Err(erro) => (Err(Error::Io(file_path.to_string(), erro)), false),
+        },
+    };
+    match &lei_result {
+        Err(e) if configuracao.verbosidade != Verbosidade::Silencioso => eprintln!("{}", e),
+        Ok(_) if configuracao.verbosidade == Verbosidade::Detalhado && do_cache => {
+            eprintln!("Do cache: {}", file_path)
+        }
+        Ok(_) if configuracao.verbosidade == Verbosidade::Detalhado => {
+            eprintln!("Parseado: {}", file_path)
+        }
+        _ => {}
+    }
+    (lei_result, do_cache)
+}
+
+fn parse_bytes(
+    file_path: &str,
+    categorias: Vec<String>,
+    bytes: &[u8],
+    configuracao: Configuracao,
+) -> (Result<Lei, Error>, bool) {
+    let cache = match configuracao.cache {
+        Some(cache) => cache,
+        None => {
+            let lei_result =
+                parse_bytes_to_lei(bytes, file_path, categorias, configuracao.codificacao);
+            return (lei_result, false);
+        }
+    };
+    let impressao = impressao_digital(bytes.iter().copied());
+    if let Some(lei) = cache.busca(file_path, impressao, &categorias) {
+        return (Ok(lei), true);
+    }
+
+    let lei_result = parse_bytes_to_lei(bytes, file_path, categorias, configuracao.codificacao);
+    if let Ok(lei) = &lei_result {
+        cache.guarda(file_path, impressao, lei);
+    }
+    (lei_result, false)
+}
+
+fn group_by_folder<T>(
+    results: Vec<(String, Result<T, Error>, bool)>,
+    directory_path: &str,
+) -> (HashMap<String, Folder>, Vec<T>) {
+    let mut directories: HashMap<String, Folder> = HashMap::new();
+    let mut leis = Vec::new();
+    for (file_path, lei_result, do_cache) in results {
+        directories
+            .entry(folder_name(&file_path, directory_path))
+            .or_default()
+            .add(&file_path, &lei_result, do_cache);
+        if let Ok(lei) = lei_result {
+            leis.push(lei);
+        }
+    }
+
     (directories, leis)
 }
 
+fn folder_name(file_path: &str, directory_path: &str) -> String {
+    let categorias = categorias_from_path(Path::new(file_path), directory_path);
+    if categorias.is_empty() {
+        ".".to_string()
+    } else {
+        categorias.join("/")
+    }
+}
+
+fn categorias_from_path(file_path: &Path, directory_path: &str) -> Vec<String> {
+    file_path
+        .strip_prefix(directory_path)
+        .ok()
+        .and_then(Path::parent)
+        .map(|relative| {
+            relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn is_html_file(entry: &DirEntry) -> bool {
     entry.file_type().is_file() && entry.file_name().to_string_lossy().ends_with(".html")
 }
@@ -60,4 +396,172 @@ fn is_not_hidden(entry: &DirEntry) -> bool {
         .map_or(false, |s| entry.depth() == 0 || !s.starts_with('.'))
 }
 
+#[cfg(test)]
+mod test {
+    use crate::cache::Cache;
+    use crate::parser_executor::{
+        categorias_from_path, parse_on_directory, parse_on_directory_to, Configuracao, Folder,
+    };
+    use std::path::Path;
+    use std::sync::mpsc;
+
+    #[test]
+    fn should_count_files_per_folder() {
+        let (directories, leis) =
+            parse_on_directory("resources/integration_tests/leis", Configuracao::default());
+
+        assert_eq!(leis.len(), 3);
+        assert_eq!(directories.len(), 2);
+        assert_eq!(
+            directories["complementar"],
+            Folder {
+                total: 2,
+                parsed: 2,
+                cached: 0,
+                failed: vec![],
+            }
+        );
+        assert_eq!(
+            directories["orgânica"],
+            Folder {
+                total: 1,
+                parsed: 1,
+                cached: 0,
+                failed: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn should_keep_failed_files_in_folder() {
+        let (directories, leis) =
+            parse_on_directory("resources/unit_tests", Configuracao::default());
+
+        assert_eq!(leis.len(), 4);
+        let folder = &directories["."];
+        assert_eq!(folder.total, 5);
+        assert_eq!(folder.parsed, 4);
+        assert_eq!(
+            folder.failed,
+            vec!["resources/unit_tests/Leis_sem_titulo_comh2.html"]
+        );
+    }
+
+    #[test]
+    fn should_send_each_lei_to_the_channel() {
+        let (sender, receiver) = mpsc::sync_channel(8);
+        let directories =
+            parse_on_directory_to("resources/unit_tests", Configuracao::default(), sender);
+
+        assert_eq!(receiver.iter().count(), 4);
+        assert_eq!(directories["."].parsed, 4);
+        assert_eq!(
+            directories["."].failed,
+            vec!["resources/unit_tests/Leis_sem_titulo_comh2.html"]
+        );
+    }
+
+    #[test]
+    fn should_not_panic_when_the_receiver_is_dropped() {
+        let (sender, receiver) = mpsc::sync_channel(8);
+        drop(receiver);
+        let directories =
+            parse_on_directory_to("resources/unit_tests", Configuracao::default(), sender);
+
+        // as threads que já estavam parseando terminam, mas nenhuma lei chega ao canal
+        assert!(directories.values().all(|folder| folder.parsed <= 4));
+    }
+
+    #[test]
+    fn should_count_directory_walk_errors_as_failures() {
+        let (directories, leis) =
+            parse_on_directory("resources/nao_existe", Configuracao::default());
+
+        assert!(leis.is_empty());
+        assert_eq!(
+            directories["."],
+            Folder {
+                total: 1,
+                parsed: 0,
+                cached: 0,
+                failed: vec!["resources/nao_existe".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn should_reuse_unchanged_files_from_cache() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let caminho = temp.path().join("cache.json");
+        let parse = || {
+            let cache = Cache::abre(&caminho, None).unwrap();
+            let configuracao = Configuracao {
+                cache: Some(&cache),
+                ..Configuracao::default()
+            };
+            let resultado = parse_on_directory("resources/integration_tests/leis", configuracao);
+            cache.grava().unwrap();
+            resultado
+        };
+
+        let (primeira, leis) = parse();
+        let (segunda, leis_do_cache) = parse();
+
+        assert_eq!(primeira["complementar"].cached, 0);
+        assert_eq!(
+            segunda["complementar"],
+            Folder {
+                total: 2,
+                parsed: 2,
+                cached: 2,
+                failed: vec![],
+            }
+        );
+        assert_eq!(segunda["orgânica"].cached, 1);
+        assert_eq!(leis_do_cache.len(), leis.len());
+        assert!(leis.iter().all(|lei| leis_do_cache.contains(lei)));
+    }
+
+    #[test]
+    fn should_parse_html_files_inside_archives() {
+        for archive_path in &[
+            "resources/integration_tests/leis.zip",
+            "resources/integration_tests/leis.tar.gz",
+        ] {
+            let (directories, leis) = parse_on_directory(archive_path, Configuracao::default());
+
+            assert_eq!(leis.len(), 3);
+            assert_eq!(directories["complementar"].parsed, 2);
+            assert_eq!(directories["orgânica"].parsed, 1);
+            assert!(leis
+                .iter()
+                .any(|lei| lei.categorias == vec!["orgânica".to_string()]));
+        }
+    }
+
+    #[test]
+    fn should_count_unreadable_archive_as_failure() {
+        let (directories, leis) =
+            parse_on_directory("resources/nao_existe.zip", Configuracao::default());
+
+        assert!(leis.is_empty());
+        assert_eq!(directories["."].failed, vec!["resources/nao_existe.zip"]);
+    }
+
+    #[test]
+    fn should_use_nested_folders_relative_to_root_as_categorias() {
+        assert_eq!(
+            categorias_from_path(
+                Path::new("dump/decretos/1995/Decreto-5907-1995.html"),
+                "dump"
+            ),
+            vec!["decretos", "1995"]
+        );
+        assert_eq!(
+            categorias_from_path(Path::new("dump/Decreto-5907-1995.html"), "dump/"),
+            Vec::<String>::new()
+        );
+    }
+}
+
 // TODO: create test for files with different type than HTML
@@ -0,0 +1,184 @@
+use crate::identificacao::remove_acentos;
+use std::iter;
+
+// em ordem alfabética, para a busca binária, e já sem acentos
+const STOP_WORDS: [&str; 61] = [
+    "a", "ao", "aos", "as", "ate", "com", "como", "da", "das", "de", "do", "dos", "e", "ela",
+    "elas", "ele", "eles", "em", "entre", "essa", "essas", "esse", "esses", "esta", "estas",
+    "este", "estes", "isso", "isto", "ja", "lhe", "lhes", "mais", "mas", "na", "nao", "nas", "no",
+    "nos", "o", "os", "ou", "para", "pela", "pelas", "pelo", "pelos", "por", "quando", "que", "se",
+    "sem", "ser", "seu", "seus", "sob", "sobre", "sua", "suas", "um", "uma",
+];
+
+// (sufixo, tamanho mínimo do que sobra, substituto), na ordem em que são testados, como nos
+// passos do RSLP (Orengo e Huyck), adaptados para palavras já sem acentos
+const PLURAL: [(&str, usize, &str); 8] = [
+    ("oes", 1, "ao"),
+    ("aes", 1, "ao"),
+    ("ais", 1, "al"),
+    ("eis", 2, "el"),
+    ("ois", 1, "ol"),
+    ("res", 3, "r"),
+    ("ns", 1, "m"),
+    ("s", 2, ""),
+];
+const FEMININO: [(&str, usize, &str); 8] = [
+    ("eira", 3, "eiro"),
+    ("inha", 3, "inho"),
+    ("ora", 3, "or"),
+    ("osa", 3, "oso"),
+    ("ica", 3, "ico"),
+    ("ada", 2, "ado"),
+    ("ida", 3, "ido"),
+    ("iva", 3, "ivo"),
+];
+const SUFIXOS: [(&str, usize, &str); 15] = [
+    ("amento", 3, ""),
+    ("imento", 4, ""),
+    ("mente", 4, ""),
+    ("idade", 4, ""),
+    ("izacao", 3, ""),
+    ("acao", 3, ""),
+    ("icao", 3, ""),
+    ("ando", 3, ""),
+    ("endo", 3, ""),
+    ("indo", 3, ""),
+    ("ado", 3, ""),
+    ("ido", 3, ""),
+    ("ar", 3, ""),
+    ("er", 3, ""),
+    ("ir", 3, ""),
+];
+
+// uma palavra do texto, com a posição em bytes e o termo indexado, que é `None` nas stop-words
+#[derive(Debug, PartialEq)]
+pub struct Palavra {
+    pub inicio: usize,
+    pub fim: usize,
+    pub termo: Option<String>,
+}
+
+pub fn palavras(texto: &str) -> Vec<Palavra> {
+    let mut palavras = Vec::new();
+    let mut inicio = None;
+    for (posicao, caractere) in texto.char_indices().chain(iter::once((texto.len(), ' '))) {
+        match (inicio, e_letra(caractere)) {
+            (None, true) => inicio = Some(posicao),
+            (Some(comeco), false) => {
+                palavras.push(Palavra {
+                    inicio: comeco,
+                    fim: posicao,
+                    termo: termo(&texto[comeco..posicao]),
+                });
+                inicio = None;
+            }
+            _ => {}
+        }
+    }
+    palavras
+}
+
+// o termo é o radical da palavra sem acentos e em minúsculas, para que "PREVIDÊNCIA" e
+// "previdencia" caiam no mesmo termo
+pub fn termo(palavra: &str) -> Option<String> {
+    let palavra = remove_acentos(palavra).to_lowercase();
+    let letra_solta = palavra.chars().count() == 1 && !palavra.chars().all(char::is_numeric);
+    if letra_solta || STOP_WORDS.binary_search(&palavra.as_str()).is_ok() {
+        None
+    } else {
+        Some(radical(&palavra))
+    }
+}
+
+pub fn radical(palavra: &str) -> String {
+    let mut radical = palavra.to_string();
+    if radical.chars().count() <= 3 || radical.chars().any(char::is_numeric) {
+        return radical;
+    }
+    if radical.ends_with('s') && !radical.ends_with("ss") && !radical.ends_with("us") {
+        substitui(&mut radical, &PLURAL);
+    }
+    if radical.ends_with('a') {
+        substitui(&mut radical, &FEMININO);
+    }
+    if !substitui(&mut radical, &SUFIXOS)
+        && radical.ends_with(['a', 'e', 'o'])
+        && radical.chars().count() > 3
+    {
+        radical.pop();
+    }
+    radical
+}
+
+fn e_letra(caractere: char) -> bool {
+    caractere.is_alphanumeric() && caractere != 'º' && caractere != 'ª'
+}
+
+// aplica a primeira regra cujo sufixo deixe um radical grande o bastante
+fn substitui(palavra: &mut String, regras: &[(&str, usize, &str)]) -> bool {
+    for (sufixo, minimo, substituto) in regras {
+        if let Some(radical) = palavra.strip_suffix(*sufixo) {
+            if radical.chars().count() >= *minimo {
+                *palavra = format!("{}{}", radical, substituto);
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use crate::radical::{palavras, radical, termo, Palavra, STOP_WORDS};
+
+    #[test]
+    fn should_keep_stop_words_sorted_for_binary_search() {
+        let mut ordenadas = STOP_WORDS.to_vec();
+        ordenadas.sort_unstable();
+
+        assert_eq!(ordenadas, STOP_WORDS.to_vec());
+    }
+
+    #[test]
+    fn should_reduce_inflections_to_the_same_radical() {
+        assert_eq!(radical("pensoes"), radical("pensao"));
+        assert_eq!(radical("servidores"), "servidor");
+        assert_eq!(radical("municipais"), "municipal");
+        assert_eq!(radical("leis"), "lei");
+        assert_eq!(radical("alterada"), radical("alterar"));
+        assert_eq!(radical("regulamentacao"), radical("regulamentar"));
+        assert_eq!(radical("2019"), "2019");
+    }
+
+    #[test]
+    fn should_ignore_accents_case_and_stop_words() {
+        assert_eq!(termo("PREVIDÊNCIA"), termo("previdencia"));
+        assert_eq!(termo("Por"), None);
+        assert_eq!(termo("n"), None);
+        assert_eq!(termo("5"), Some("5".to_string()));
+    }
+
+    #[test]
+    fn should_split_words_keeping_their_positions() {
+        assert_eq!(
+            palavras("Art. 1ºFica"),
+            vec![
+                Palavra {
+                    inicio: 0,
+                    fim: 3,
+                    termo: Some("art".to_string()),
+                },
+                Palavra {
+                    inicio: 5,
+                    fim: 6,
+                    termo: Some("1".to_string()),
+                },
+                Palavra {
+                    inicio: 8,
+                    fim: 12,
+                    termo: Some("fic".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use crate::identificacao::{data_iso, TipoNorma};
+use crate::parser::clean_html_to_text;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref LINK_LAW_REGEX: Regex =
+        Regex::new(r#"<a\s(?P<atributos>[^>]*class="link_law"[^>]*)>"#).unwrap();
+    static ref ID_REGEX: Regex = Regex::new(r#"data-id="(?P<id>\d+)""#).unwrap();
+    static ref TOOLTIP_REGEX: Regex =
+        Regex::new(r#"data-original-title="(?P<tooltip>[^"]*)""#).unwrap();
+    static ref HREF_REGEX: Regex = Regex::new(r#"href="(?P<href>[^"]*)""#).unwrap();
+    static ref URL_NORMA_REGEX: Regex =
+        Regex::new(r"/(?P<tipo>[a-z-]+)/(?P<ano>\d{4})/\d+/(?P<numero>\d+)/").unwrap();
+    static ref DATA_DA_NORMA_REGEX: Regex =
+        Regex::new(r"Data da Norma:\s*(?P<data>\d{1,2}\.\d{1,2}\.\d{4})\s*(?:-\s*(?P<ementa>.*))?")
+            .unwrap();
+}
+
+/// Uma norma citada no texto da lei, a partir dos links do Leis Municipais.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Referencia {
+    /// Identificador da norma citada no Leis Municipais.
+    pub id: Option<u64>,
+    /// Link para a norma citada.
+    pub url: Option<String>,
+    /// Tipo da norma citada.
+    pub tipo: Option<TipoNorma>,
+    /// Número da norma citada.
+    pub numero: Option<u32>,
+    /// Ano da norma citada.
+    pub ano: Option<i32>,
+    /// Data da norma citada no formato ISO 8601.
+    pub data: Option<String>,
+    /// Ementa da norma citada.
+    pub ementa: Option<String>,
+    /// Posição da citação no texto da lei, em caracteres.
+    pub posicao: usize,
+}
+
+pub fn parse_referencias(texto_html: &str) -> Vec<Referencia> {
+    let mut posicao = 0;
+    let mut anterior = 0;
+
+    LINK_LAW_REGEX
+        .captures_iter(texto_html)
+        .map(|captures| {
+            let inicio = captures.get(0).unwrap().start();
+            posicao += clean_html_to_text(&texto_html[anterior..inicio])
+                .chars()
+                .count();
+            anterior = inicio;
+            parse_referencia(&captures["atributos"], posicao)
+        })
+        .collect()
+}
+
+fn parse_referencia(atributos: &str, posicao: usize) -> Referencia {
+    let url = HREF_REGEX
+        .captures(atributos)
+        .map(|captures| url_da_norma(&captures["href"]));
+    let (tipo, numero, ano) = match url.as_ref().and_then(|url| URL_NORMA_REGEX.captures(url)) {
+        Some(norma) => (
+            TipoNorma::from_descricao(&norma["tipo"].replace('-', " ")),
+            norma["numero"].parse().ok(),
+            norma["ano"].parse().ok(),
+        ),
+        None => (None, None, None),
+    };
+
+    let tooltip = TOOLTIP_REGEX
+        .captures(atributos)
+        .map(|captures| captures["tooltip"].to_string())
+        .unwrap_or_default();
+    let (data, ementa) = match DATA_DA_NORMA_REGEX.captures(&tooltip) {
+        Some(captures) => (
+            data_iso(&captures["data"]),
+            captures
+                .name("ementa")
+                .map(|ementa| clean_html_to_text(ementa.as_str().trim())),
+        ),
+        None => (None, None),
+    };
+
+    Referencia {
+        id: ID_REGEX
+            .captures(atributos)
+            .and_then(|captures| captures["id"].parse().ok()),
+        url,
+        tipo,
+        numero,
+        ano,
+        data,
+        ementa,
+        posicao,
+    }
+}
+
+// os links exportados vêm com o domínio duplicado
+// (https://leismunicipais.com.brhttp://www2.leismunicipais.com.br/...)
+fn url_da_norma(href: &str) -> String {
+    href.rfind("http")
+        .map_or(href, |inicio| &href[inicio..])
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::identificacao::TipoNorma;
+    use crate::referencias::{parse_referencias, Referencia};
+
+    #[test]
+    fn should_parse_referencias_from_link_law_anchors() {
+        let html = "Altera a Lei Complementar nº <a class=\"link_law\" data-id=\"1592745\" \
+            data-original-title=\" Data da Norma: 10.04.2002 - ALTERA O REGIME DE PREVIDÊNCIA.\" \
+            data-toggle=\"tooltip\" href=\"https://leismunicipais.com.brhttp://www2.leismunicipais.com.br/a/ba/f/feira-de-santana/lei-complementar/2002/1/11/lei-complementar-n-11-2002\" \
+            rel=\"tooltip\">11</a>/2002<br><br>e a Lei nº <a class=\"link_law\" data-id=\"1618357\" \
+            href=\"http://www2.leismunicipais.com.br/a/ba/f/feira-de-santana/lei-ordinaria/1995/180/1802/lei-ordinaria-n-1802-1995\">1802</a>";
+
+        assert_eq!(
+            parse_referencias(html),
+            vec![
+                Referencia {
+                    id: Some(1_592_745),
+                    url: Some("http://www2.leismunicipais.com.br/a/ba/f/feira-de-santana/lei-complementar/2002/1/11/lei-complementar-n-11-2002".to_string()),
+                    tipo: Some(TipoNorma::LeiComplementar),
+                    numero: Some(11),
+                    ano: Some(2002),
+                    data: Some("2002-04-10".to_string()),
+                    ementa: Some("ALTERA O REGIME DE PREVIDÊNCIA.".to_string()),
+                    posicao: 28,
+                },
+                Referencia {
+                    id: Some(1_618_357),
+                    url: Some("http://www2.leismunicipais.com.br/a/ba/f/feira-de-santana/lei-ordinaria/1995/180/1802/lei-ordinaria-n-1802-1995".to_string()),
+                    tipo: Some(TipoNorma::Lei),
+                    numero: Some(1802),
+                    ano: Some(1995),
+                    data: None,
+                    ementa: None,
+                    posicao: 47,
+                },
+            ]
+        );
+    }
+}
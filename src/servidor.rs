@@ -0,0 +1,477 @@
+//! API HTTP somente leitura com as leis parseadas, servida em localhost.
+//!
+//! As rotas respondem JSON:
+//!
+//! - `GET /leis?pagina=1&por_pagina=20&categoria=...&tipo=decreto&ano=1995` lista as leis,
+//!   com paginação e filtros opcionais;
+//! - `GET /leis/{id}` devolve a lei completa;
+//! - `GET /leis/{id}/artigos/{numero}` devolve um artigo da lei, como `1` ou `5-A`;
+//! - `GET /busca?q=...` busca as leis com o [`Indice`] do módulo [`busca`](crate::busca), com a
+//!   mesma paginação da listagem e, em cada lei, a pontuação e o trecho encontrado.
+//!
+//! O id de cada lei é o mesmo nome das páginas do [`site`](crate::site), como
+//! `lei-complementar-122-2019`.
+
+use crate::busca::Indice;
+use crate::error::Error;
+use crate::exportacao::nome_serializado;
+use crate::parser::Lei;
+use crate::site::nomes_das_paginas;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io;
+use std::str::FromStr;
+use tiny_http::{Header, Method, Response, Server};
+
+const POR_PAGINA_PADRAO: usize = 20;
+const POR_PAGINA_MAXIMO: usize = 100;
+
+/// Resposta de uma requisição à [`Api`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Resposta {
+    /// Código de status HTTP.
+    pub status: u16,
+    /// Corpo da resposta. Os erros vêm como `{"erro": "..."}`.
+    pub corpo: Value,
+}
+
+impl Resposta {
+    fn ok(corpo: Value) -> Resposta {
+        Resposta { status: 200, corpo }
+    }
+
+    fn erro(status: u16, mensagem: &str) -> Resposta {
+        Resposta {
+            status,
+            corpo: json!({ "erro": mensagem }),
+        }
+    }
+}
+
+/// As leis carregadas em memória e as rotas da API.
+///
+/// As requisições podem ser respondidas diretamente com [`Api::responde`], sem abrir uma
+/// porta, ou servidas por HTTP com [`Api::serve`].
+///
+/// ```
+/// use leis_municipais::exportacao::le_leis;
+/// use leis_municipais::servidor::Api;
+/// use std::path::Path;
+///
+/// let leis = le_leis(Path::new("resources/integration_tests/leis.json")).unwrap();
+/// let api = Api::new(leis);
+///
+/// let resposta = api.responde("/leis?tipo=decreto");
+/// assert_eq!(resposta.status, 200);
+/// assert_eq!(resposta.corpo["total"], 2);
+/// ```
+pub struct Api {
+    leis: Vec<Lei>,
+    ids: Vec<String>,
+    indices: HashMap<String, usize>,
+    indice_de_busca: Indice,
+}
+
+impl Api {
+    /// Indexa as leis para a API.
+    #[must_use]
+    pub fn new(leis: Vec<Lei>) -> Api {
+        let ids = nomes_das_paginas(&leis);
+        let indices = ids
+            .iter()
+            .enumerate()
+            .map(|(indice, id)| (id.clone(), indice))
+            .collect();
+        let indice_de_busca = Indice::new(&leis);
+        Api {
+            leis,
+            ids,
+            indices,
+            indice_de_busca,
+        }
+    }
+
+    /// Responde a um `GET` na `url`, que é o caminho com a query string, como
+    /// `/leis?pagina=2`.
+    #[must_use]
+    pub fn responde(&self, url: &str) -> Resposta {
+        let mut partes = url.splitn(2, '?');
+        let caminho = partes.next().unwrap_or_default();
+        let parametros = parametros(partes.next().unwrap_or_default());
+        let segmentos = caminho
+            .split('/')
+            .filter(|segmento| !segmento.is_empty())
+            .map(decodifica)
+            .collect::<Vec<String>>();
+
+        let resultado = match segmentos
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<&str>>()
+            .as_slice()
+        {
+            [] => Ok(rotas()),
+            ["leis"] => self.lista(&parametros),
+            ["leis", id] => self.lei(id),
+            ["leis", id, "artigos", numero] => self.artigo(id, numero),
+            ["busca"] => self.busca(&parametros),
+            _ => Err(Resposta::erro(404, "Rota não encontrada")),
+        };
+        match resultado {
+            Ok(resposta) | Err(resposta) => resposta,
+        }
+    }
+
+    /// Serve a API por HTTP no `endereco`, como `127.0.0.1:8000`, até o processo ser
+    /// encerrado. Só o método `GET` é aceito.
+    ///
+    /// # Errors
+    ///
+    /// Retorna [`Error::Servidor`] se não for possível escutar no endereço.
+    pub fn serve(&self, endereco: &str) -> Result<(), Error> {
+        let servidor = Server::http(endereco).map_err(|erro| {
+            Error::Servidor(
+                endereco.to_string(),
+                io::Error::new(io::ErrorKind::Other, erro),
+            )
+        })?;
+        for requisicao in servidor.incoming_requests() {
+            let resposta = if *requisicao.method() == Method::Get {
+                self.responde(requisicao.url())
+            } else {
+                Resposta::erro(405, "Apenas o método GET é aceito")
+            };
+            // um cliente que desconectou antes da resposta não afeta os demais
+            let _ = requisicao.respond(resposta_http(&resposta));
+        }
+        Ok(())
+    }
+
+    fn lista(&self, parametros: &HashMap<String, String>) -> Result<Resposta, Resposta> {
+        let paginacao = Paginacao::de(parametros)?;
+        let ano = parametros
+            .get("ano")
+            .map(|ano| {
+                ano.parse::<i32>()
+                    .map_err(|_| Resposta::erro(400, "O parâmetro ano deve ser um número"))
+            })
+            .transpose()?;
+        let categoria = parametros.get("categoria");
+        let tipo = parametros.get("tipo");
+
+        let leis = (0..self.leis.len())
+            .filter(|indice| {
+                let lei = &self.leis[*indice];
+                categoria.map_or(true, |categoria| lei.categoria == *categoria)
+                    && tipo.map_or(true, |tipo| {
+                        lei.identificacao
+                            .tipo
+                            .as_ref()
+                            .and_then(nome_serializado)
+                            .as_deref()
+                            == Some(tipo.as_str())
+                    })
+                    && ano.map_or(true, |ano| lei.identificacao.ano == Some(ano))
+            })
+            .map(|indice| self.resumo(indice))
+            .collect();
+        Ok(Resposta::ok(paginacao.aplica(leis)))
+    }
+
+    fn lei(&self, id: &str) -> Result<Resposta, Resposta> {
+        let indice = self.indice(id)?;
+        let mut corpo = json!(self.leis[indice]);
+        if let Value::Object(campos) = &mut corpo {
+            campos.insert("id".to_string(), json!(id));
+        }
+        Ok(Resposta::ok(corpo))
+    }
+
+    fn artigo(&self, id: &str, numero: &str) -> Result<Resposta, Resposta> {
+        let indice = self.indice(id)?;
+        let numero = numero_do_artigo(numero);
+        self.leis[indice]
+            .artigos
+            .iter()
+            .find(|artigo| numero_do_artigo(&artigo.rotulo) == numero)
+            .map(|artigo| Resposta::ok(json!({ "lei": id, "artigo": artigo })))
+            .ok_or_else(|| Resposta::erro(404, "Artigo não encontrado"))
+    }
+
+    fn busca(&self, parametros: &HashMap<String, String>) -> Result<Resposta, Resposta> {
+        let paginacao = Paginacao::de(parametros)?;
+        let consulta = parametros.get("q").map_or("", String::as_str);
+        if consulta.trim().is_empty() {
+            return Err(Resposta::erro(
+                400,
+                "Informe os termos da busca no parâmetro q",
+            ));
+        }
+
+        let busca = self
+            .indice_de_busca
+            .busca(consulta, paginacao.pular(), paginacao.por_pagina);
+        let leis = busca
+            .resultados
+            .into_iter()
+            .map(|resultado| {
+                let mut resumo = self.resumo(self.indices[&resultado.id]);
+                resumo["pontuacao"] = json!(resultado.pontuacao);
+                resumo["trecho"] = json!(resultado.trecho);
+                resumo
+            })
+            .collect();
+        Ok(Resposta::ok(paginacao.corpo(busca.total, leis)))
+    }
+
+    fn indice(&self, id: &str) -> Result<usize, Resposta> {
+        self.indices
+            .get(id)
+            .copied()
+            .ok_or_else(|| Resposta::erro(404, "Lei não encontrada"))
+    }
+
+    fn resumo(&self, indice: usize) -> Value {
+        let lei = &self.leis[indice];
+        json!({
+            "id": self.ids[indice],
+            "titulo": lei.titulo,
+            "tipo": lei.identificacao.tipo,
+            "numero": lei.identificacao.numero,
+            "ano": lei.identificacao.ano,
+            "data": lei.identificacao.data,
+            "categoria": lei.categoria,
+            "resumo": lei.resumo,
+        })
+    }
+}
+
+struct Paginacao {
+    pagina: usize,
+    por_pagina: usize,
+}
+
+impl Paginacao {
+    fn de(parametros: &HashMap<String, String>) -> Result<Paginacao, Resposta> {
+        let pagina = numero(parametros, "pagina", 1)?;
+        let por_pagina = numero(parametros, "por_pagina", POR_PAGINA_PADRAO)?;
+        if pagina == 0 || por_pagina == 0 {
+            return Err(Resposta::erro(
+                400,
+                "Os parâmetros pagina e por_pagina devem ser maiores que zero",
+            ));
+        }
+        Ok(Paginacao {
+            pagina,
+            por_pagina: por_pagina.min(POR_PAGINA_MAXIMO),
+        })
+    }
+
+    fn aplica(&self, leis: Vec<Value>) -> Value {
+        let total = leis.len();
+        let leis = leis
+            .into_iter()
+            .skip(self.pular())
+            .take(self.por_pagina)
+            .collect();
+        self.corpo(total, leis)
+    }
+
+    fn pular(&self) -> usize {
+        (self.pagina - 1).saturating_mul(self.por_pagina)
+    }
+
+    fn corpo(&self, total: usize, leis: Vec<Value>) -> Value {
+        json!({
+            "total": total,
+            "pagina": self.pagina,
+            "por_pagina": self.por_pagina,
+            "leis": leis,
+        })
+    }
+}
+
+fn numero<T: FromStr>(
+    parametros: &HashMap<String, String>,
+    nome: &str,
+    padrao: T,
+) -> Result<T, Resposta> {
+    parametros.get(nome).map_or(Ok(padrao), |valor| {
+        valor
+            .parse()
+            .map_err(|_| Resposta::erro(400, &format!("O parâmetro {} deve ser um número", nome)))
+    })
+}
+
+fn rotas() -> Resposta {
+    Resposta::ok(json!({
+        "rotas": [
+            "/leis?pagina=&por_pagina=&categoria=&tipo=&ano=",
+            "/leis/{id}",
+            "/leis/{id}/artigos/{numero}",
+            "/busca?q=&pagina=&por_pagina=",
+        ]
+    }))
+}
+
+fn resposta_http(resposta: &Resposta) -> Response<io::Cursor<Vec<u8>>> {
+    Response::from_string(resposta.corpo.to_string())
+        .with_status_code(resposta.status)
+        .with_header(cabecalho("Content-Type", "application/json; charset=utf-8"))
+        .with_header(cabecalho("Access-Control-Allow-Origin", "*"))
+}
+
+fn cabecalho(nome: &str, valor: &str) -> Header {
+    Header::from_bytes(nome.as_bytes(), valor.as_bytes()).expect("Cabeçalho HTTP inválido")
+}
+
+fn parametros(consulta: &str) -> HashMap<String, String> {
+    consulta
+        .split('&')
+        .filter(|parametro| !parametro.is_empty())
+        .map(|parametro| {
+            let mut partes = parametro.splitn(2, '=');
+            let nome = decodifica(partes.next().unwrap_or_default());
+            let valor = decodifica(partes.next().unwrap_or_default());
+            (nome, valor)
+        })
+        .collect()
+}
+
+// decodifica o percent-encoding da URL, com `+` como espaço, como os formulários enviam
+fn decodifica(valor: &str) -> String {
+    let mut bytes = Vec::with_capacity(valor.len());
+    let mut indice = 0;
+    while indice < valor.len() {
+        let byte = valor.as_bytes()[indice];
+        let hexadecimal = valor
+            .get(indice + 1..indice + 3)
+            .and_then(|hexadecimal| u8::from_str_radix(hexadecimal, 16).ok());
+        match (byte, hexadecimal) {
+            (b'%', Some(decodificado)) => {
+                bytes.push(decodificado);
+                indice += 3;
+            }
+            (b'+', _) => {
+                bytes.push(b' ');
+                indice += 1;
+            }
+            _ => {
+                bytes.push(byte);
+                indice += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+// `Art. 5º-A.` e `5-a` viram `5-A`
+fn numero_do_artigo(rotulo: &str) -> String {
+    rotulo
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .trim_end_matches(|c: char| c == '.' || c.is_whitespace())
+        .replace(|c: char| c == 'º' || c == '°', "")
+        .trim_end_matches('o')
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exportacao::le_leis;
+    use crate::servidor::{decodifica, numero_do_artigo, Api};
+    use std::path::Path;
+
+    fn api() -> Api {
+        Api::new(le_leis(Path::new("resources/integration_tests/leis.json")).unwrap())
+    }
+
+    #[test]
+    fn should_list_leis_with_pagination_and_filters() {
+        let api = api();
+
+        let todas = api.responde("/leis").corpo;
+        assert_eq!(todas["total"], 3);
+        assert_eq!(todas["leis"][0]["id"], "lei-complementar-122-2019");
+
+        let decretos = api
+            .responde("/leis?tipo=decreto&por_pagina=1&pagina=2")
+            .corpo;
+        assert_eq!(decretos["total"], 2);
+        assert_eq!(decretos["pagina"], 2);
+        assert_eq!(decretos["leis"].as_array().unwrap().len(), 1);
+        assert_eq!(decretos["leis"][0]["id"], "decreto-5907-1995");
+
+        let organica = api.responde("/leis?categoria=org%C3%A2nica&ano=2019").corpo;
+        assert_eq!(organica["total"], 1);
+        assert_eq!(api.responde("/leis?ano=1990").corpo["total"], 0);
+    }
+
+    #[test]
+    fn should_reject_invalid_parameters() {
+        let api = api();
+
+        let resposta = api.responde("/leis?ano=mil");
+        assert_eq!(resposta.status, 400);
+        assert_eq!(resposta.corpo["erro"], "O parâmetro ano deve ser um número");
+        assert_eq!(api.responde("/leis?pagina=0").status, 400);
+        assert_eq!(api.responde("/busca?q=+").status, 400);
+        assert_eq!(api.responde("/normas").status, 404);
+    }
+
+    #[test]
+    fn should_fetch_lei_and_artigo_by_id() {
+        let api = api();
+
+        let lei = api.responde("/leis/lei-complementar-122-2019");
+        assert_eq!(lei.status, 200);
+        assert_eq!(lei.corpo["id"], "lei-complementar-122-2019");
+        assert_eq!(lei.corpo["numero"], 122);
+        assert_eq!(lei.corpo["artigos"].as_array().unwrap().len(), 10);
+
+        let artigo = api
+            .responde("/leis/lei-complementar-122-2019/artigos/10")
+            .corpo;
+        assert_eq!(artigo["lei"], "lei-complementar-122-2019");
+        assert_eq!(artigo["artigo"]["rotulo"], "Art. 10");
+        assert_eq!(
+            api.responde("/leis/decreto-1-1984/artigos/1%C2%BA").corpo["artigo"]["rotulo"],
+            "Art. 1º"
+        );
+
+        assert_eq!(api.responde("/leis/decreto-2-1984").status, 404);
+        assert_eq!(api.responde("/leis/decreto-1-1984/artigos/4").status, 404);
+    }
+
+    #[test]
+    fn should_search_with_the_index_and_paginate() {
+        let api = api();
+
+        let resposta = api.responde("/busca?q=PENSAO+por+morte").corpo;
+        assert_eq!(resposta["total"], 1);
+        assert_eq!(resposta["leis"][0]["id"], "lei-complementar-122-2019");
+        assert!(resposta["leis"][0]["trecho"]
+            .as_str()
+            .unwrap()
+            .contains("**pensão** por **morte**"));
+
+        let regimento = api.responde("/busca?q=regimento").corpo;
+        assert_eq!(regimento["total"], 2);
+        assert_eq!(regimento["leis"][0]["id"], "decreto-5907-1995");
+        assert!(regimento["leis"][0]["pontuacao"].as_f64().unwrap() > 0.0);
+        assert_eq!(
+            api.responde("/busca?q=regimento&por_pagina=1&pagina=2")
+                .corpo["leis"][0]["id"],
+            "decreto-1-1984"
+        );
+    }
+
+    #[test]
+    fn should_decode_urls_and_artigo_numbers() {
+        assert_eq!(decodifica("org%C3%A2nica+1%2F84"), "orgânica 1/84");
+        assert_eq!(decodifica("100%"), "100%");
+        assert_eq!(numero_do_artigo("Art. 5º-A."), "5-A");
+        assert_eq!(numero_do_artigo("5-a"), "5-A");
+        assert_eq!(numero_do_artigo("Art. 1o"), "1");
+    }
+}
@@ -0,0 +1,544 @@
+//! Geração de um site estático com as leis parseadas.
+
+use crate::error::Error;
+use crate::estrutura::{Dispositivo, TipoDispositivo};
+use crate::exportacao::erro_de_io;
+use crate::exportacao_markdown::nome_da_norma;
+use crate::identificacao::TipoNorma;
+use crate::parser::Lei;
+use crate::referencias::Referencia;
+use crate::xml::escapa;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const ESTILO: &str = "body {
+  font-family: Georgia, serif;
+  line-height: 1.6;
+  max-width: 48rem;
+  margin: 0 auto;
+  padding: 1rem;
+  color: #1a1a1a;
+}
+a { color: #0645ad; }
+a:focus { outline: 3px solid #ffbf47; }
+.pular { position: absolute; left: -999rem; }
+.pular:focus { left: 1rem; }
+.resumo { font-style: italic; }
+.artigo { margin-top: 1.5rem; }
+.paragrafo, .inciso, .alinea, .item { margin-left: 1.5rem; }
+.rotulo { font-weight: bold; text-decoration: none; }
+#busca { width: 100%; font-size: 1rem; padding: 0.5rem; }
+dt { font-weight: bold; }
+";
+
+// o índice vem de um <script> e não de um fetch, que os navegadores bloqueiam em páginas
+// abertas direto do disco (file://)
+const BUSCA: &str = r#"<script src="busca.js"></script>
+<script>
+const normaliza = texto => texto.normalize('NFD').replace(/[\u0300-\u036f]/g, '').toLowerCase();
+const campo = document.getElementById('busca');
+const resultados = document.getElementById('resultados');
+LEIS.forEach(lei => { lei.chave = normaliza(lei.titulo + ' ' + lei.resumo); });
+campo.addEventListener('input', () => {
+  const termo = normaliza(campo.value.trim());
+  resultados.innerHTML = '';
+  if (!termo) { return; }
+  LEIS.filter(lei => lei.chave.includes(termo)).slice(0, 50).forEach(lei => {
+    const item = document.createElement('li');
+    const link = document.createElement('a');
+    link.href = lei.pagina;
+    link.textContent = lei.titulo;
+    item.appendChild(link);
+    resultados.appendChild(item);
+  });
+});
+</script>"#;
+
+#[derive(Serialize)]
+struct EntradaDeBusca<'a> {
+    titulo: &'a str,
+    resumo: &'a str,
+    categoria: &'a str,
+    ano: Option<i32>,
+    pagina: String,
+}
+
+type Norma = (TipoNorma, u32, i32);
+
+struct Site<'a> {
+    leis: &'a [Lei],
+    paginas: Vec<String>,
+    normas: HashMap<Norma, usize>,
+    citada_por: Vec<Vec<usize>>,
+}
+
+/// Gera na pasta um site estático com as leis: um índice por categoria e ano, uma página por
+/// lei com âncoras para cada artigo, links entre as leis que se citam e um índice de busca.
+///
+/// # Errors
+///
+/// Retorna [`Error::Io`] se algum arquivo do site não puder ser gravado.
+pub fn escreve_paginas(leis: &[Lei], diretorio: &Path) -> Result<(), Error> {
+    let site = Site::new(leis);
+    let pasta_das_leis = diretorio.join("leis");
+    fs::create_dir_all(&pasta_das_leis).map_err(|erro| erro_de_io(&pasta_das_leis, erro))?;
+
+    escreve_arquivo(&diretorio.join("estilo.css"), |saida| {
+        saida.write_all(ESTILO.as_bytes())
+    })?;
+    escreve_arquivo(&diretorio.join("index.html"), |saida| {
+        site.escreve_indice(saida)
+    })?;
+    escreve_arquivo(&diretorio.join("busca.js"), |saida| {
+        site.escreve_busca(saida)
+    })?;
+    for (indice, pagina) in site.paginas.iter().enumerate() {
+        escreve_arquivo(&pasta_das_leis.join(format!("{}.html", pagina)), |saida| {
+            site.escreve_pagina(saida, indice)
+        })?;
+    }
+    Ok(())
+}
+
+impl<'a> Site<'a> {
+    fn new(leis: &'a [Lei]) -> Site<'a> {
+        let paginas = nomes_das_paginas(leis);
+        let mut normas = HashMap::new();
+        for (indice, lei) in leis.iter().enumerate() {
+            let identificacao = &lei.identificacao;
+            if let (Some(tipo), Some(numero), Some(ano)) =
+                (identificacao.tipo, identificacao.numero, identificacao.ano)
+            {
+                normas.entry((tipo, numero, ano)).or_insert(indice);
+            }
+        }
+
+        let mut citada_por = vec![Vec::new(); leis.len()];
+        for (indice, lei) in leis.iter().enumerate() {
+            let mut citadas = lei
+                .referencias
+                .iter()
+                .filter_map(|referencia| norma(referencia).and_then(|norma| normas.get(&norma)))
+                .filter(|citada| **citada != indice)
+                .copied()
+                .collect::<Vec<usize>>();
+            citadas.sort_unstable();
+            citadas.dedup();
+            for citada in citadas {
+                citada_por[citada].push(indice);
+            }
+        }
+
+        Site {
+            leis,
+            paginas,
+            normas,
+            citada_por,
+        }
+    }
+
+    fn escreve_indice(&self, saida: &mut dyn Write) -> io::Result<()> {
+        let mut por_categoria = BTreeMap::new();
+        for (indice, lei) in self.leis.iter().enumerate() {
+            por_categoria
+                .entry(lei.categoria.as_str())
+                .or_insert_with(BTreeMap::new)
+                .entry(Reverse(lei.identificacao.ano))
+                .or_insert_with(Vec::new)
+                .push(indice);
+        }
+
+        escreve_cabecalho(saida, "Leis municipais", "")?;
+        writeln!(saida, "<h1>Leis municipais</h1>")?;
+        writeln!(saida, r#"<form role="search" onsubmit="return false">"#)?;
+        writeln!(
+            saida,
+            r#"<label for="busca">Buscar por título ou resumo</label>"#
+        )?;
+        writeln!(
+            saida,
+            r#"<input id="busca" type="search" autocomplete="off">"#
+        )?;
+        writeln!(saida, "</form>")?;
+        writeln!(saida, r#"<ul id="resultados" aria-live="polite"></ul>"#)?;
+
+        writeln!(saida, r#"<nav aria-label="Categorias"><ul>"#)?;
+        for (numero, categoria) in (1..).zip(por_categoria.keys()) {
+            writeln!(
+                saida,
+                r##"<li><a href="#categoria-{}">{}</a></li>"##,
+                numero,
+                escapa(categoria)
+            )?;
+        }
+        writeln!(saida, "</ul></nav>")?;
+
+        for (numero, (categoria, por_ano)) in (1..).zip(&por_categoria) {
+            writeln!(
+                saida,
+                r#"<section aria-labelledby="categoria-{0}"><h2 id="categoria-{0}">{1}</h2>"#,
+                numero,
+                escapa(categoria)
+            )?;
+            for (Reverse(ano), indices) in por_ano {
+                let ano = ano.map_or_else(|| "Sem ano".to_string(), |ano| ano.to_string());
+                writeln!(saida, "<h3>{}</h3>", ano)?;
+                writeln!(saida, "<ul>")?;
+                for indice in indices {
+                    writeln!(
+                        saida,
+                        r#"<li><a href="leis/{}.html">{}</a></li>"#,
+                        self.paginas[*indice],
+                        escapa(&self.leis[*indice].titulo)
+                    )?;
+                }
+                writeln!(saida, "</ul>")?;
+            }
+            writeln!(saida, "</section>")?;
+        }
+        writeln!(saida, "{}", BUSCA)?;
+        escreve_rodape(saida)
+    }
+
+    fn escreve_busca(&self, saida: &mut dyn Write) -> io::Result<()> {
+        let entradas = self
+            .leis
+            .iter()
+            .zip(&self.paginas)
+            .map(|(lei, pagina)| EntradaDeBusca {
+                titulo: &lei.titulo,
+                resumo: &lei.resumo,
+                categoria: &lei.categoria,
+                ano: lei.identificacao.ano,
+                pagina: format!("leis/{}.html", pagina),
+            })
+            .collect::<Vec<EntradaDeBusca>>();
+        write!(saida, "const LEIS = ")?;
+        serde_json::to_writer(&mut *saida, &entradas).map_err(io::Error::from)?;
+        writeln!(saida, ";")
+    }
+
+    fn escreve_pagina(&self, saida: &mut dyn Write, indice: usize) -> io::Result<()> {
+        let lei = &self.leis[indice];
+        let identificacao = &lei.identificacao;
+
+        escreve_cabecalho(saida, &lei.titulo, "../")?;
+        writeln!(saida, r#"<p><a href="../index.html">Todas as leis</a></p>"#)?;
+        writeln!(saida, "<h1>{}</h1>", escapa(&lei.titulo))?;
+        writeln!(saida, r#"<p class="resumo">{}</p>"#, escapa(&lei.resumo))?;
+
+        writeln!(saida, "<dl>")?;
+        if let Some(tipo) = identificacao.tipo {
+            writeln!(saida, "<dt>Tipo</dt><dd>{}</dd>", escapa(&tipo.to_string()))?;
+        }
+        if let Some(numero) = identificacao.numero {
+            writeln!(saida, "<dt>Número</dt><dd>{}</dd>", numero)?;
+        }
+        if let Some(data) = &identificacao.data {
+            writeln!(
+                saida,
+                r#"<dt>Data</dt><dd><time datetime="{0}">{0}</time></dd>"#,
+                escapa(data)
+            )?;
+        }
+        writeln!(
+            saida,
+            "<dt>Categoria</dt><dd>{}</dd>",
+            escapa(&lei.categorias.join(" / "))
+        )?;
+        if let Some(documento) = &lei.documento {
+            writeln!(
+                saida,
+                r#"<dt>Documento original</dt><dd><a href="{0}">{0}</a></dd>"#,
+                escapa(documento)
+            )?;
+        }
+        writeln!(saida, "</dl>")?;
+
+        writeln!(saida, r#"<article aria-label="Texto">"#)?;
+        if lei.artigos.is_empty() {
+            escreve_linhas(saida, &lei.texto)?;
+        }
+        for (numero, artigo) in (1..).zip(&lei.artigos) {
+            escreve_dispositivo(saida, artigo, None, numero)?;
+        }
+        writeln!(saida, "</article>")?;
+
+        if !lei.referencias.is_empty() {
+            writeln!(saida, "<h2>Normas citadas</h2>")?;
+            writeln!(saida, "<ul>")?;
+            for referencia in &lei.referencias {
+                self.escreve_referencia(saida, referencia)?;
+            }
+            writeln!(saida, "</ul>")?;
+        }
+
+        if !self.citada_por[indice].is_empty() {
+            writeln!(saida, "<h2>Citada por</h2>")?;
+            writeln!(saida, "<ul>")?;
+            for citante in &self.citada_por[indice] {
+                writeln!(
+                    saida,
+                    r#"<li><a href="{}.html">{}</a></li>"#,
+                    self.paginas[*citante],
+                    escapa(&self.leis[*citante].titulo)
+                )?;
+            }
+            writeln!(saida, "</ul>")?;
+        }
+        escreve_rodape(saida)
+    }
+
+    // cita a página da própria lei quando ela faz parte do site, e o link original quando não
+    fn escreve_referencia(&self, saida: &mut dyn Write, referencia: &Referencia) -> io::Result<()> {
+        let descricao = descricao_da_referencia(referencia);
+        let link = norma(referencia)
+            .and_then(|norma| self.normas.get(&norma))
+            .map(|indice| format!("{}.html", self.paginas[*indice]))
+            .or_else(|| referencia.url.clone());
+        match link {
+            Some(link) => writeln!(
+                saida,
+                r#"<li><a href="{}">{}</a></li>"#,
+                escapa(&link),
+                escapa(&descricao)
+            ),
+            None => writeln!(saida, "<li>{}</li>", escapa(&descricao)),
+        }
+    }
+}
+
+// tipo-numero-ano, com um sufixo quando duas leis teriam o mesmo nome
+pub(crate) fn nomes_das_paginas(leis: &[Lei]) -> Vec<String> {
+    let mut usados = HashMap::new();
+    (1..)
+        .zip(leis)
+        .map(|(posicao, lei)| {
+            let nome = nome_da_norma(lei).unwrap_or_else(|| format!("norma-{}", posicao));
+            let repeticoes = usados.entry(nome.clone()).or_insert(0);
+            *repeticoes += 1;
+            if *repeticoes == 1 {
+                nome
+            } else {
+                format!("{}-{}", nome, repeticoes)
+            }
+        })
+        .collect()
+}
+
+fn norma(referencia: &Referencia) -> Option<Norma> {
+    Some((referencia.tipo?, referencia.numero?, referencia.ano?))
+}
+
+fn descricao_da_referencia(referencia: &Referencia) -> String {
+    let norma = match (referencia.tipo, referencia.numero, referencia.ano) {
+        (Some(tipo), Some(numero), Some(ano)) => format!("{} nº {}/{}", tipo, numero, ano),
+        (Some(tipo), Some(numero), None) => format!("{} nº {}", tipo, numero),
+        _ => "Norma".to_string(),
+    };
+    match &referencia.ementa {
+        Some(ementa) if !ementa.is_empty() => format!("{} – {}", norma, ementa),
+        _ => norma,
+    }
+}
+
+// as âncoras seguem a hierarquia, como em art-1-par-2-inc-3
+fn escreve_dispositivo(
+    saida: &mut dyn Write,
+    dispositivo: &Dispositivo,
+    pai: Option<&str>,
+    numero: usize,
+) -> io::Result<()> {
+    let (classe, prefixo) = classe(dispositivo.tipo);
+    let ancora = match pai {
+        Some(pai) => format!("{}-{}-{}", pai, prefixo, numero),
+        None => format!("{}-{}", prefixo, numero),
+    };
+
+    if dispositivo.tipo == TipoDispositivo::Artigo {
+        writeln!(saida, r#"<section class="{}" id="{}">"#, classe, ancora)?;
+        writeln!(
+            saida,
+            r##"<h2><a class="rotulo" href="#{}">{}</a></h2>"##,
+            ancora,
+            escapa(&dispositivo.rotulo)
+        )?;
+        escreve_linhas(saida, &dispositivo.texto)?;
+    } else {
+        writeln!(saida, r#"<div class="{}" id="{}">"#, classe, ancora)?;
+        let mut linhas = dispositivo.texto.lines();
+        writeln!(
+            saida,
+            r##"<p><a class="rotulo" href="#{}">{}</a> {}</p>"##,
+            ancora,
+            escapa(&dispositivo.rotulo),
+            escapa(linhas.next().unwrap_or_default())
+        )?;
+        escreve_linhas(saida, &linhas.collect::<Vec<&str>>().join("\n"))?;
+    }
+
+    for (numero, filho) in (1..).zip(&dispositivo.filhos) {
+        escreve_dispositivo(saida, filho, Some(&ancora), numero)?;
+    }
+    if dispositivo.tipo == TipoDispositivo::Artigo {
+        writeln!(saida, "</section>")
+    } else {
+        writeln!(saida, "</div>")
+    }
+}
+
+fn classe(tipo: TipoDispositivo) -> (&'static str, &'static str) {
+    match tipo {
+        TipoDispositivo::Artigo => ("artigo", "art"),
+        TipoDispositivo::Paragrafo => ("paragrafo", "par"),
+        TipoDispositivo::Inciso => ("inciso", "inc"),
+        TipoDispositivo::Alinea => ("alinea", "ali"),
+        TipoDispositivo::Item => ("item", "item"),
+    }
+}
+
+fn escreve_linhas(saida: &mut dyn Write, texto: &str) -> io::Result<()> {
+    for linha in texto
+        .lines()
+        .map(str::trim)
+        .filter(|linha| !linha.is_empty())
+    {
+        writeln!(saida, "<p>{}</p>", escapa(linha))?;
+    }
+    Ok(())
+}
+
+fn escreve_cabecalho(saida: &mut dyn Write, titulo: &str, raiz: &str) -> io::Result<()> {
+    writeln!(saida, "<!DOCTYPE html>")?;
+    writeln!(saida, r#"<html lang="pt-BR">"#)?;
+    writeln!(saida, "<head>")?;
+    writeln!(saida, r#"<meta charset="utf-8">"#)?;
+    writeln!(
+        saida,
+        r#"<meta name="viewport" content="width=device-width, initial-scale=1">"#
+    )?;
+    writeln!(saida, "<title>{}</title>", escapa(titulo))?;
+    writeln!(
+        saida,
+        r#"<link rel="stylesheet" href="{}estilo.css">"#,
+        raiz
+    )?;
+    writeln!(saida, "</head>")?;
+    writeln!(saida, "<body>")?;
+    writeln!(
+        saida,
+        r##"<a class="pular" href="#conteudo">Pular para o conteúdo</a>"##
+    )?;
+    writeln!(saida, r#"<main id="conteudo">"#)
+}
+
+fn escreve_rodape(saida: &mut dyn Write) -> io::Result<()> {
+    writeln!(saida, "</main>")?;
+    writeln!(saida, "</body>")?;
+    writeln!(saida, "</html>")
+}
+
+fn escreve_arquivo(
+    caminho: &Path,
+    escreve: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> Result<(), Error> {
+    let arquivo = File::create(caminho).map_err(|erro| erro_de_io(caminho, erro))?;
+    let mut saida = BufWriter::new(arquivo);
+    escreve(&mut saida)
+        .and_then(|_| saida.flush())
+        .map_err(|erro| erro_de_io(caminho, erro))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::identificacao::{Identificacao, TipoNorma};
+    use crate::parser::{parse_html_to_lei, Lei};
+    use crate::site::{escreve_paginas, nomes_das_paginas};
+    use assert_fs::TempDir;
+    use std::fs;
+
+    fn lei_complementar() -> Lei {
+        parse_html_to_lei(
+            "resources/unit_tests/LeisMunicipais-com-br-Lei-Complementar-122-2019.html",
+            vec!["complementar".to_string()],
+            None,
+        )
+        .unwrap()
+    }
+
+    fn lei_citada() -> Lei {
+        Lei {
+            titulo: "LEI COMPLEMENTAR Nº 11, DE 10 DE ABRIL DE 2002".to_string(),
+            identificacao: Identificacao {
+                tipo: Some(TipoNorma::LeiComplementar),
+                numero: Some(11),
+                ano: Some(2002),
+                data: Some("2002-04-10".to_string()),
+            },
+            categoria: "previdência".to_string(),
+            categorias: vec!["previdência".to_string()],
+            referencias: vec![],
+            ..lei_complementar()
+        }
+    }
+
+    #[test]
+    fn should_name_pages_after_tipo_numero_and_ano_without_collisions() {
+        let sem_identificacao = Lei {
+            identificacao: Identificacao::default(),
+            ..lei_complementar()
+        };
+
+        assert_eq!(
+            nomes_das_paginas(&[lei_complementar(), sem_identificacao, lei_complementar()]),
+            vec![
+                "lei-complementar-122-2019",
+                "norma-2",
+                "lei-complementar-122-2019-2"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_write_index_pages_with_anchors_links_and_search_index() {
+        let temp = TempDir::new().unwrap();
+        let diretorio = temp.path().join("site");
+
+        escreve_paginas(&[lei_complementar(), lei_citada()], &diretorio).unwrap();
+
+        let indice = fs::read_to_string(diretorio.join("index.html")).unwrap();
+        assert!(indice.contains(r#"<html lang="pt-BR">"#));
+        assert!(indice.contains(r#"<h2 id="categoria-1">complementar</h2>"#));
+        assert!(indice.contains(r#"<h2 id="categoria-2">previdência</h2>"#));
+        assert!(indice.contains("<h3>2019</h3>"));
+        assert!(indice.contains(r#"<a href="leis/lei-complementar-122-2019.html">"#));
+
+        let pagina =
+            fs::read_to_string(diretorio.join("leis/lei-complementar-122-2019.html")).unwrap();
+        assert!(pagina.contains(r##"<section class="artigo" id="art-10">"##));
+        assert!(pagina.contains(r##"<a class="rotulo" href="#art-1">Art. 1º</a>"##));
+        assert!(pagina.contains(r#"<a href="lei-complementar-11-2002.html">"#));
+
+        let citada =
+            fs::read_to_string(diretorio.join("leis/lei-complementar-11-2002.html")).unwrap();
+        assert!(citada.contains("<h2>Citada por</h2>"));
+        assert!(citada.contains(r#"<a href="lei-complementar-122-2019.html">"#));
+
+        assert!(indice.contains(r#"<script src="busca.js"></script>"#));
+        let busca = fs::read_to_string(diretorio.join("busca.js")).unwrap();
+        let busca: serde_json::Value = serde_json::from_str(
+            busca
+                .strip_prefix("const LEIS = ")
+                .and_then(|busca| busca.strip_suffix(";\n"))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(busca.as_array().unwrap().len(), 2);
+        assert_eq!(busca[0]["pagina"], "leis/lei-complementar-122-2019.html");
+        assert!(diretorio.join("estilo.css").exists());
+    }
+}
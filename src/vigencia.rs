@@ -0,0 +1,308 @@
+use crate::parser::clean_html_to_text;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    static ref TAG_REGEX: Regex =
+        Regex::new(r"(?i)<(?P<fecha>/)?(?P<nome>s|strike|del|span|font)\b(?P<atributos>[^>]*)>")
+            .unwrap();
+    static ref CLASSE_REGEX: Regex = Regex::new(
+        r#"(?i)class\s*=\s*(?:"(?P<aspas>[^"]*)"|'(?P<apostrofo>[^']*)'|(?P<valor>[^\s>]+))"#
+    )
+    .unwrap();
+    static ref REDACAO_DADA_REGEX: Regex =
+        Regex::new(r"(?i)\(\s*reda[çc][ãa]o\s+dada\s+pel[oa]s?\b[^)]*\)").unwrap();
+}
+
+/// Situação de um trecho do texto da lei.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Situacao {
+    /// O trecho está em vigor.
+    Vigente,
+    /// O trecho foi revogado e não aparece no texto consolidado.
+    Revogado,
+    /// O trecho foi alterado por outra norma.
+    Alterado,
+}
+
+/// Um trecho do texto da lei marcado com sua situação.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Trecho {
+    /// Situação do trecho.
+    pub situacao: Situacao,
+    /// Início do trecho no texto da lei, em caracteres.
+    pub inicio: usize,
+    /// Fim do trecho no texto da lei, em caracteres (exclusivo).
+    pub fim: usize,
+}
+
+// o texto é o `texto_html` já limpo, onde são procuradas as anotações de redação dada
+pub fn parse_trechos(texto_html: &str, texto: &str) -> Vec<Trecho> {
+    let mut trechos = Vec::new();
+    let mut abertas: Vec<(String, Option<Situacao>)> = Vec::new();
+    let mut posicao = 0;
+    let mut anterior = 0;
+
+    for tag in TAG_REGEX.captures_iter(texto_html) {
+        let inicio = tag.get(0).unwrap().start();
+        posicao = adiciona_trecho(
+            &mut trechos,
+            situacao_atual(&abertas),
+            posicao,
+            &texto_html[anterior..inicio],
+        );
+        anterior = inicio;
+
+        let nome = tag["nome"].to_lowercase();
+        if tag.name("fecha").is_some() {
+            if let Some(indice) = abertas.iter().rposition(|(aberta, _)| *aberta == nome) {
+                abertas.truncate(indice);
+            }
+        } else {
+            let situacao = situacao_da_tag(&nome, &tag["atributos"]);
+            abertas.push((nome, situacao));
+        }
+    }
+    adiciona_trecho(
+        &mut trechos,
+        situacao_atual(&abertas),
+        posicao,
+        &texto_html[anterior..],
+    );
+
+    for anotacao in REDACAO_DADA_REGEX.find_iter(texto) {
+        let inicio_da_linha = texto[..anotacao.start()]
+            .rfind('\n')
+            .map_or(0, |quebra| quebra + 1);
+        trechos = altera(
+            &trechos,
+            texto[..inicio_da_linha].chars().count(),
+            texto[..anotacao.end()].chars().count(),
+        );
+    }
+    trechos
+}
+
+pub fn texto_consolidado(texto: &str, trechos: &[Trecho]) -> String {
+    let caracteres = texto.chars().collect::<Vec<char>>();
+    trechos
+        .iter()
+        .filter(|trecho| trecho.situacao != Situacao::Revogado)
+        .flat_map(|trecho| caracteres.get(trecho.inicio..trecho.fim).unwrap_or(&[]))
+        .collect()
+}
+
+fn adiciona_trecho(
+    trechos: &mut Vec<Trecho>,
+    situacao: Situacao,
+    inicio: usize,
+    html: &str,
+) -> usize {
+    let fim = inicio + clean_html_to_text(html).chars().count();
+    if fim == inicio {
+        return fim;
+    }
+
+    if let Some(ultimo) = trechos
+        .last_mut()
+        .filter(|ultimo| ultimo.situacao == situacao)
+    {
+        ultimo.fim = fim;
+    } else {
+        trechos.push(Trecho {
+            situacao,
+            inicio,
+            fim,
+        });
+    }
+    fim
+}
+
+// a linha que termina com "(Redação dada pela ...)" foi alterada, exceto o que estiver riscado
+fn altera(trechos: &[Trecho], inicio: usize, fim: usize) -> Vec<Trecho> {
+    let mut alterados: Vec<Trecho> = Vec::new();
+    for trecho in trechos {
+        let cortes = [
+            trecho.inicio,
+            inicio.clamp(trecho.inicio, trecho.fim),
+            fim.clamp(trecho.inicio, trecho.fim),
+            trecho.fim,
+        ];
+        for par in cortes.windows(2).filter(|par| par[0] < par[1]) {
+            let situacao =
+                if trecho.situacao == Situacao::Vigente && par[0] >= inicio && par[1] <= fim {
+                    Situacao::Alterado
+                } else {
+                    trecho.situacao
+                };
+            match alterados.last_mut() {
+                Some(ultimo) if ultimo.situacao == situacao => ultimo.fim = par[1],
+                _ => alterados.push(Trecho {
+                    situacao,
+                    inicio: par[0],
+                    fim: par[1],
+                }),
+            }
+        }
+    }
+    alterados
+}
+
+// o negrito só destaca o texto; alterado é o que vem em `class="v"` ou com a redação dada
+fn situacao_da_tag(nome: &str, atributos: &str) -> Option<Situacao> {
+    let classe_v = CLASSE_REGEX.captures(atributos).map_or(false, |captures| {
+        ["aspas", "apostrofo", "valor"]
+            .iter()
+            .filter_map(|grupo| captures.name(grupo))
+            .any(|classe| classe.as_str().split_whitespace().any(|valor| valor == "v"))
+    });
+
+    match nome {
+        "s" | "strike" | "del" => Some(Situacao::Revogado),
+        _ if classe_v => Some(Situacao::Alterado),
+        _ => None,
+    }
+}
+
+// o CSS exportado pinta `.v s` como riscado, então o revogado prevalece
+fn situacao_atual(abertas: &[(String, Option<Situacao>)]) -> Situacao {
+    let situacoes = abertas
+        .iter()
+        .filter_map(|(_, situacao)| *situacao)
+        .collect::<Vec<Situacao>>();
+    if situacoes.contains(&Situacao::Revogado) {
+        Situacao::Revogado
+    } else if situacoes.is_empty() {
+        Situacao::Vigente
+    } else {
+        Situacao::Alterado
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_html_to_lei;
+    use crate::vigencia::{parse_trechos, texto_consolidado, Situacao, Trecho};
+
+    // sem espaços junto às tags, que o clean_html_to_text descarta
+    #[test]
+    fn should_classify_trechos_as_vigente_revogado_and_alterado() {
+        let html = "Art. 1º Caput.<br><s>I - revogado;</s><br>\
+            <span class=\"v\">II - nova redação (<s>riscada</s>);</span><br>\
+            III - alterado (<s>texto anterior</s>) (Redação dada pela Lei nº 2/2020)<br>\
+            <b>IV - em destaque.</b>";
+        let texto = "Art. 1º Caput.\nI - revogado;\nII - nova redação (riscada);\n\
+            III - alterado (texto anterior) (Redação dada pela Lei nº 2/2020)\nIV - em destaque.";
+
+        let trechos = parse_trechos(html, texto);
+        assert_eq!(
+            trechos,
+            vec![
+                Trecho {
+                    situacao: Situacao::Vigente,
+                    inicio: 0,
+                    fim: 15,
+                },
+                Trecho {
+                    situacao: Situacao::Revogado,
+                    inicio: 15,
+                    fim: 28,
+                },
+                Trecho {
+                    situacao: Situacao::Vigente,
+                    inicio: 28,
+                    fim: 29,
+                },
+                Trecho {
+                    situacao: Situacao::Alterado,
+                    inicio: 29,
+                    fim: 48,
+                },
+                Trecho {
+                    situacao: Situacao::Revogado,
+                    inicio: 48,
+                    fim: 55,
+                },
+                Trecho {
+                    situacao: Situacao::Alterado,
+                    inicio: 55,
+                    fim: 57,
+                },
+                Trecho {
+                    situacao: Situacao::Vigente,
+                    inicio: 57,
+                    fim: 58,
+                },
+                Trecho {
+                    situacao: Situacao::Alterado,
+                    inicio: 58,
+                    fim: 74,
+                },
+                Trecho {
+                    situacao: Situacao::Revogado,
+                    inicio: 74,
+                    fim: 88,
+                },
+                Trecho {
+                    situacao: Situacao::Alterado,
+                    inicio: 88,
+                    fim: 123,
+                },
+                Trecho {
+                    situacao: Situacao::Vigente,
+                    inicio: 123,
+                    fim: 141,
+                },
+            ]
+        );
+        assert_eq!(
+            texto_consolidado(texto, &trechos),
+            "Art. 1º Caput.\n\nII - nova redação ();\n\
+             III - alterado () (Redação dada pela Lei nº 2/2020)\nIV - em destaque."
+        );
+    }
+
+    #[test]
+    fn should_read_trechos_from_a_lei_with_struck_through_text() {
+        let lei = parse_html_to_lei(
+            "resources/unit_tests/Leis_com_trechos_revogados.html",
+            vec![],
+            None,
+        )
+        .unwrap();
+        let trecho = |situacao| {
+            let caracteres = lei.texto.chars().collect::<Vec<char>>();
+            lei.trechos
+                .iter()
+                .filter(|trecho| trecho.situacao == situacao)
+                .map(|trecho| caracteres[trecho.inicio..trecho.fim].iter().collect())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(
+            trecho(Situacao::Revogado),
+            vec![
+                "§ 2º A habilitação posterior que importe inclusão ou exclusão de dependente só \
+                 produzirá efeitos a contar da data da inscrição ou habilitação."
+            ]
+        );
+        assert_eq!(
+            trecho(Situacao::Alterado),
+            vec![
+                "§ 1º A importância total assim obtida será rateada em partes iguais entre todos \
+                 os dependentes com direito a pensão, e não será protelada pela falta de \
+                 habilitação de outro possível dependente.",
+                "Art. 10.Esta Lei Complementar entra em vigor na data de sua publicação, \
+                 revogadas as disposições em contrário. (Redação dada pela Lei Complementar nº \
+                 130/2020)"
+            ]
+        );
+        assert!(lei.texto.contains("§ 2º A habilitação posterior"));
+        assert!(!lei
+            .texto_consolidado
+            .contains("§ 2º A habilitação posterior"));
+    }
+}
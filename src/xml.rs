@@ -0,0 +1,54 @@
+pub const DECLARACAO: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+pub fn escapa(texto: &str) -> String {
+    let mut escapado = String::with_capacity(texto.len());
+    for caractere in texto.chars() {
+        match caractere {
+            '&' => escapado.push_str("&amp;"),
+            '<' => escapado.push_str("&lt;"),
+            '>' => escapado.push_str("&gt;"),
+            '"' => escapado.push_str("&quot;"),
+            '\'' => escapado.push_str("&apos;"),
+            _ => escapado.push(caractere),
+        }
+    }
+    escapado
+}
+
+// usado nos testes ignorados dos exportadores, que precisam do xmllint e dos esquemas oficiais
+// baixados em resources/ (veja a seção Testes do README)
+#[cfg(test)]
+pub fn valida_esquema(xml: &str, esquema: &str) {
+    assert!(
+        std::path::Path::new(esquema).exists(),
+        "{} não encontrado: baixe o esquema oficial antes de rodar os testes ignorados",
+        esquema
+    );
+    let temp = assert_fs::TempDir::new().unwrap();
+    let arquivo = temp.path().join("documento.xml");
+    std::fs::write(&arquivo, xml).unwrap();
+    let saida = std::process::Command::new("xmllint")
+        .args(&["--noout", "--schema", esquema])
+        .arg(&arquivo)
+        .output()
+        .expect("Erro ao executar o xmllint, que precisa estar instalado");
+
+    assert!(
+        saida.status.success(),
+        "{}",
+        String::from_utf8_lossy(&saida.stderr)
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use crate::xml::escapa;
+
+    #[test]
+    fn should_escape_markup_characters() {
+        assert_eq!(
+            escapa(r#"Art. 1º <a href="x">R$ 5 & 'mais'</a>"#),
+            "Art. 1º &lt;a href=&quot;x&quot;&gt;R$ 5 &amp; &apos;mais&apos;&lt;/a&gt;"
+        );
+    }
+}
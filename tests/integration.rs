@@ -5,7 +5,7 @@ use std::fs;
 #[test]
 fn should_parser_folder_and_write_leis_to_file_as_json() {
     let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
-    cmd.arg("resources/integration_tests/leis");
+    cmd.args(&["parse", "resources/integration_tests/leis"]);
 
     cmd.assert()
         .stdout(predicate::str::contains("complementar | 2"));
@@ -26,3 +26,249 @@ fn should_parser_folder_and_write_leis_to_file_as_json() {
         _ => println!("unexpected type"),
     }
 }
+
+#[test]
+fn should_parse_leis_straight_from_a_tar_gz_archive() {
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.args(&[
+        "parse",
+        "resources/integration_tests/leis.tar.gz",
+        "--format",
+        "jsonl",
+        "--output",
+        "-",
+    ]);
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    assert_eq!(String::from_utf8(output).unwrap().lines().count(), 3);
+}
+
+#[test]
+fn should_fail_validation_when_some_file_could_not_be_parsed() {
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.args(&["validate", "resources/unit_tests"]);
+
+    cmd.assert().failure().stdout(predicate::str::contains(
+        "resources/unit_tests/Leis_sem_titulo_comh2.html",
+    ));
+}
+
+#[test]
+fn should_show_help_in_portuguese() {
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.arg("--help");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("COMANDOS:"))
+        .stdout(predicate::str::contains("Exibe esta ajuda"));
+}
+
+#[test]
+fn should_stream_leis_as_jsonl_to_stdout() {
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.args(&[
+        "parse",
+        "resources/integration_tests/leis",
+        "--format",
+        "jsonl",
+        "--output",
+        "-",
+    ]);
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let lines = String::from_utf8(output).unwrap();
+    let lines = lines.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 3);
+    lines.iter().for_each(|line| {
+        let lei: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(lei["titulo"].is_string());
+    });
+}
+
+#[test]
+fn should_reuse_cached_leis_on_the_second_run() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let cache = temp.path().join("cache.json");
+    let parse = || {
+        let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+        cmd.args(&["parse", "resources/integration_tests/leis", "--output"])
+            .arg(temp.path().join("leis.json"))
+            .arg("--cache")
+            .arg(&cache);
+        cmd.assert()
+    };
+
+    parse().success().stdout(predicate::str::contains(
+        "Do cache: 0, parseados novamente: 3",
+    ));
+    parse().success().stdout(predicate::str::contains(
+        "Do cache: 3, parseados novamente: 0",
+    ));
+}
+
+#[test]
+fn should_keep_the_cache_after_commands_that_do_not_parse() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let cache = temp.path().join("cache.json");
+    let parse = || {
+        let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+        cmd.args(&["parse", "resources/integration_tests/leis", "--output"])
+            .arg(temp.path().join("leis.json"))
+            .arg("--cache")
+            .arg(&cache);
+        cmd.assert()
+    };
+
+    parse().success();
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.args(&[
+        "export",
+        "--entrada",
+        "resources/integration_tests/leis.json",
+        "--format",
+        "csv",
+    ])
+    .arg(temp.path().join("leis.csv"))
+    .arg("--cache")
+    .arg(&cache);
+    cmd.assert().success();
+
+    parse().success().stdout(predicate::str::contains(
+        "Do cache: 3, parseados novamente: 0",
+    ));
+}
+
+#[test]
+fn should_search_leis_with_an_index_saved_on_disk() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let indice = temp.path().join("indice.json");
+    let entrada = temp.path().join("leis.json");
+    fs::copy("resources/integration_tests/leis.json", &entrada).unwrap();
+    let busca = |argumentos: &[&str]| {
+        let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+        cmd.arg("search")
+            .args(argumentos)
+            .arg("--indice")
+            .arg(&indice)
+            .arg("--entrada")
+            .arg(&entrada);
+        cmd.assert()
+    };
+
+    busca(&["PREVIDENCIA"])
+        .success()
+        .stdout(predicate::str::contains("Índice criado em"))
+        .stdout(predicate::str::contains("1 lei encontrada"))
+        .stdout(predicate::str::contains("(lei-complementar-122-2019, "))
+        .stdout(predicate::str::contains("**previd"));
+    assert!(indice.exists());
+
+    busca(&["regimento", "-n", "1"])
+        .success()
+        .stdout(predicate::str::contains("Usando o índice"))
+        .stdout(predicate::str::contains("2 leis encontradas"))
+        .stdout(predicate::str::contains("1. DECRETO"))
+        .stdout(predicate::str::contains("2. DECRETO").not());
+}
+
+#[test]
+fn should_create_the_index_again_when_the_leis_change() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let indice = temp.path().join("indice.json");
+    let entrada = temp.path().join("leis.json");
+    fs::copy("resources/integration_tests/leis.json", &entrada).unwrap();
+    let busca = || {
+        let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+        cmd.args(&["search", "regimento", "--indice"])
+            .arg(&indice)
+            .arg("--entrada")
+            .arg(&entrada);
+        cmd.assert()
+    };
+
+    busca()
+        .success()
+        .stdout(predicate::str::contains("2 leis encontradas"));
+    let leis: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&entrada).unwrap()).unwrap();
+    fs::write(&entrada, serde_json::to_string(&leis[..1]).unwrap()).unwrap();
+
+    busca()
+        .success()
+        .stdout(predicate::str::contains("Índice criado em"))
+        .stdout(predicate::str::contains("Nenhuma lei encontrada."));
+}
+
+#[test]
+fn should_export_one_lexml_document_per_lei() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let diretorio = temp.path().join("lexml");
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.args(&[
+        "export",
+        "--entrada",
+        "resources/integration_tests/leis.json",
+        "--format",
+        "lexml",
+    ])
+    .arg(&diretorio);
+
+    cmd.assert().success();
+
+    assert_eq!(fs::read_dir(&diretorio).unwrap().count(), 3);
+    let documento =
+        fs::read_to_string(diretorio.join(
+            "urn_lex_br_bahia_feira.de.santana_municipal_lei.complementar_2019-02-22_122.xml",
+        ))
+        .unwrap();
+    assert!(documento.contains(
+        "<DocumentoIndividual>urn:lex:br;bahia;feira.de.santana:municipal:lei.complementar:2019-02-22;122</DocumentoIndividual>"
+    ));
+}
+
+#[test]
+fn should_export_one_markdown_file_per_lei_in_categoria_folders() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let diretorio = temp.path().join("markdown");
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.args(&[
+        "export",
+        "--entrada",
+        "resources/integration_tests/leis.json",
+        "--format",
+        "markdown",
+    ])
+    .arg(&diretorio);
+
+    cmd.assert().success();
+
+    assert_eq!(
+        fs::read_dir(diretorio.join("complementar"))
+            .unwrap()
+            .count(),
+        2
+    );
+    let markdown =
+        fs::read_to_string(diretorio.join("organica/lei-complementar-122-2019-a76234f9.md"))
+            .unwrap();
+    assert!(markdown.starts_with("---\ntitulo: "));
+    assert!(markdown.contains("\n## Art. 1"));
+}
+
+#[test]
+fn should_generate_static_site_from_parsed_leis() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("leis-municipais").unwrap();
+    cmd.arg("site")
+        .arg(temp.path())
+        .args(&["--entrada", "resources/integration_tests/leis.json"]);
+
+    cmd.assert().success();
+
+    assert!(temp.path().join("index.html").exists());
+    assert!(temp.path().join("busca.js").exists());
+    let pagina =
+        fs::read_to_string(temp.path().join("leis/lei-complementar-122-2019.html")).unwrap();
+    assert!(pagina.contains(r#"id="art-1""#));
+}